@@ -1,11 +1,43 @@
+// `sgdkx blastem`: run the bundled BlastEm. Also home of the headless launcher used by
+// `sgdkx debug`, `test` and `capture`, and by editors through `sgdkx blastem --gdb-server`: a
+// BlastEm without a window (BLASTEM_NO_GUI + SDL's dummy audio driver keep a headless launch
+// from stalling on an audio error dialog), on a free TCP port, watched for its readiness line.
+
+pub mod control;
+pub mod gdb_remote;
+
+use crate::error::{Error, OrExit, Result};
 use crate::path;
+use crate::project::{self, Component};
 use clap::Parser;
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 pub struct Args {
+    /// Start BlastEm headless as a gdb server for the ROM (first argument) and print the port
+    /// once it waits for the debugger; runs until BlastEm exits
+    #[arg(long = "gdb-server")]
+    gdb_server: bool,
+
+    /// TCP port for --gdb-server (default: a free one)
+    #[arg(long, requires = "gdb_server")]
+    port: Option<u16>,
+
+    /// Seconds to wait for --gdb-server to become ready
+    #[arg(long, value_name = "SECS", requires = "gdb_server", default_value_t = READY_TIMEOUT.as_secs())]
+    timeout: u64,
+
+    /// Report the --gdb-server port as JSON (`{"port": ..., "pid": ...}`)
+    #[arg(long, requires = "gdb_server")]
+    json: bool,
+
     /// Arguments passed straight through to BlastEm (e.g. out/rom.bin)
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
@@ -13,20 +45,263 @@ pub struct Args {
 
 /// Thin wrapper: locate the bundled BlastEm and exec it with the given args verbatim.
 pub fn run(args: &Args) {
-    let exe = match find_blastem(&path::config_dir()) {
-        Some(p) => p,
-        None => {
-            eprintln!("BlastEm not found. Please run `sgdkx install` first.");
-            std::process::exit(1);
-        }
-    };
+    let exe = require_blastem(Path::new(".")).or_exit();
+    if args.gdb_server {
+        return serve_gdb(&exe, args);
+    }
     let status = Command::new(&exe)
         .args(&args.args)
         .status()
-        .expect("Failed to run BlastEm");
+        .map_err(|e| Error::io("failed to run BlastEm", e))
+        .or_exit();
     std::process::exit(status.code().unwrap_or(1));
 }
 
+/// `--gdb-server`: start the server, report where it listens — one line, `gdb server listening
+/// on localhost:<port>` (what the generated VS Code task waits for) or JSON — and stay in the
+/// foreground until BlastEm exits.
+fn serve_gdb(exe: &Path, args: &Args) {
+    let Some((rom, extra)) = args.args.split_first() else {
+        Error::InvalidArgument("--gdb-server needs the ROM to run (e.g. out/debug/rom.bin)".into())
+            .exit();
+    };
+    let timeout = Duration::from_secs(args.timeout);
+    let mut server = start_gdb_server(exe, Path::new(rom), extra, args.port, false, timeout).or_exit();
+    if args.json {
+        let report = serde_json::json!({ "port": server.port, "pid": server.process.id() });
+        println!("{report}");
+    } else {
+        println!("gdb server listening on localhost:{}", server.port);
+    }
+    let status = server.process.wait().or_exit();
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// How long a headless BlastEm gets to become ready by default.
+pub const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What the gdb-capable BlastEm prints once it listens for the debugger.
+pub const GDB_READY_LINE: &str = "Waiting for GDB connection";
+
+/// A TCP port on localhost that nothing listens on right now.
+pub fn free_port() -> Result<u16> {
+    TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|l| l.local_addr())
+        .map(|a| a.port())
+        .map_err(|e| Error::io("cannot find a free TCP port", e))
+}
+
+/// A decimal or `0x`-prefixed hex number.
+pub fn parse_number(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Bytes written as pairs of hex digits.
+fn parse_hex_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// A Command running BlastEm `exe` headless: no window (`BLASTEM_NO_GUI`), no audio device
+/// (`SDL_AUDIODRIVER=dummy`), output piped for `Headless::spawn`.
+pub fn headless_command(exe: &Path) -> Command {
+    let mut cmd = Command::new(exe);
+    cmd.env("BLASTEM_NO_GUI", "1")
+        .env("SDL_AUDIODRIVER", "dummy")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cmd
+}
+
+/// Put `cmd` in its own process group, so a ^C at the terminal — meant for gdb, which
+/// interrupts the target with it — doesn't stop BlastEm too.
+pub fn detach(cmd: &mut Command) {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::creation_flags(cmd, 0x0000_0200); // CREATE_NEW_PROCESS_GROUP
+}
+
+/// A running headless BlastEm. Its stdout and stderr are read line by line until it is ready
+/// (`wait_for`) and discarded afterwards; it is killed when dropped.
+pub struct Headless {
+    child: Child,
+    lines: mpsc::Receiver<String>,
+    capturing: Arc<AtomicBool>,
+}
+
+impl Headless {
+    /// Spawn `cmd` (from `headless_command`).
+    pub fn spawn(mut cmd: Command) -> Result<Headless> {
+        let mut child = cmd.spawn().map_err(|e| Error::io("failed to run BlastEm", e))?;
+        // both streams feed one channel, which closes when BlastEm exits
+        let (tx, lines) = mpsc::channel();
+        let capturing = Arc::new(AtomicBool::new(true));
+        let streams: [Option<Box<dyn Read + Send>>; 2] = [
+            child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+            child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>),
+        ];
+        for stream in streams.into_iter().flatten() {
+            let (tx, capturing) = (tx.clone(), capturing.clone());
+            std::thread::spawn(move || {
+                // keep reading after capture stops: a full pipe would block BlastEm
+                for line in BufReader::new(stream).lines().map_while(std::result::Result::ok) {
+                    if capturing.load(Ordering::Relaxed) {
+                        let _ = tx.send(line);
+                    }
+                }
+            });
+        }
+        Ok(Headless {
+            child,
+            lines,
+            capturing,
+        })
+    }
+
+    /// Wait until an output line contains `needle`. Earlier lines are echoed to stderr (they
+    /// usually say why a launch fails). An error if BlastEm exits first or `timeout` passes.
+    pub fn wait_for(&mut self, needle: &str, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(left) {
+                Ok(line) if line.contains(needle) => {
+                    self.ready();
+                    return Ok(());
+                }
+                Ok(line) => eprintln!("{line}"),
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    self.stop();
+                    return Err(Error::Emulator(format!(
+                        "BlastEm not ready after {}s (no \"{needle}\")",
+                        timeout.as_secs()
+                    )));
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    let status = self.child.wait().map_or(String::new(), |s| format!(" ({s})"));
+                    return Err(Error::Emulator(format!(
+                        "BlastEm exited before \"{needle}\"{status}"
+                    )));
+                }
+            }
+        }
+    }
+
+    /// BlastEm is up: stop capturing its output (from here on it is noise).
+    fn ready(&mut self) {
+        self.capturing.store(false, Ordering::Relaxed);
+        while self.lines.try_recv().is_ok() {}
+    }
+
+    /// Echo the output captured so far to stderr.
+    fn echo_output(&self) {
+        self.lines.try_iter().for_each(|line| eprintln!("{line}"));
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Whether BlastEm has already exited.
+    pub fn exited(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Wait for BlastEm to exit on its own.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        self.child.wait().map_err(|e| Error::io("failed to wait for BlastEm", e))
+    }
+
+    /// Stop BlastEm now (a no-op if it already exited).
+    pub fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Headless {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A headless BlastEm serving gdb on localhost:`port`.
+pub struct GdbServer {
+    pub port: u16,
+    pub process: Headless,
+}
+
+/// Start BlastEm `exe` headless as a gdb server for `rom` (plus BlastEm `args`) on `port` — a
+/// free one if None — and return once it waits for the debugger (`GDB_READY_LINE`), or fail
+/// after `timeout`. `detached` keeps terminal ^C away from it (see `detach`). Needs the
+/// debug-capable BlastEm build, which honors `-D` and `BLASTEM_GDB_PORT`.
+pub fn start_gdb_server(
+    exe: &Path,
+    rom: &Path,
+    args: &[String],
+    port: Option<u16>,
+    detached: bool,
+    timeout: Duration,
+) -> Result<GdbServer> {
+    let busy = |port| {
+        Error::Emulator(format!(
+            "port {port} is already in use — pick another with --port, or stop the debug session \
+             using it"
+        ))
+    };
+    if let Some(port) = port.filter(|p| port_in_use(*p)) {
+        return Err(busy(port));
+    }
+    // an auto-picked port is only free when picked: if something takes it before BlastEm
+    // binds it, pick again
+    const ATTEMPTS: u32 = 3;
+    let mut attempt = 1;
+    loop {
+        let (port, picked) = match port {
+            Some(p) => (p, false),
+            None => (free_port()?, true),
+        };
+        let mut cmd = headless_command(exe);
+        cmd.arg(rom).arg("-D").args(args).env("BLASTEM_GDB_PORT", port.to_string());
+        if detached {
+            detach(&mut cmd);
+        }
+        let mut process = Headless::spawn(cmd)?;
+        match process.wait_for(GDB_READY_LINE, timeout) {
+            Ok(()) => return Ok(GdbServer { port, process }),
+            Err(_) if port_in_use(port) && picked && attempt < ATTEMPTS => attempt += 1,
+            Err(_) if port_in_use(port) => return Err(busy(port)),
+            Err(e) => {
+                return Err(Error::Emulator(format!(
+                    "{e} — is it the debug-capable build? (`sgdkx install --only blastem`)"
+                )));
+            }
+        }
+    }
+}
+
+/// Whether something listens on localhost:`port` (after a failed launch: what kept BlastEm
+/// from binding it).
+fn port_in_use(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+/// The BlastEm to run for `project`: its pin (if any) checked against the install, then the
+/// executable located.
+pub fn require_blastem(project: &Path) -> Result<PathBuf> {
+    project::require_pinned(project, Component::Blastem)?;
+    find_blastem(&path::try_config_dir()?).ok_or(Error::ToolNotFound("BlastEm"))
+}
+
 /// Locate the native BlastEm executable under <config>/blastem, regardless of the
 /// extracted layout (macOS: BlastEm.app/Contents/MacOS/blastem; Linux:
 /// blastem-linux-*/blastem; Windows: blastem-win64-*/blastem.exe).
@@ -0,0 +1,147 @@
+// Client for the control socket `sgdkx capture` drives BlastEm through — the one place that
+// speaks its protocol. Reading memory and stopping at code addresses go through BlastEm's gdb
+// server instead (`gdb_remote`); what this adds is what the gdb protocol can't do: step whole
+// frames, hold controller buttons, and read the rendered frame.
+//
+// BlastEm started with `BLASTEM_CONTROL_PORT=<port>` listens on TCP localhost:<port> and is
+// paused until a client drives it. The protocol is line based, ASCII, one request → one reply:
+//
+//   request:  <command> [<arg> ...]\n          numbers are decimal or 0x-prefixed hex
+//   reply:    ok [<value> ...]\n  |  error <message>\n
+//
+//   frames <n>                    run n frames, pause     → ok <frame>
+//   input <pad> <buttons>         hold buttons on pad 1/2 until changed (`A+START`, `none`)
+//                                                         → ok
+//   framebuffer                   the last frame          → ok <width> <height> <hex RGB888 rows>
+//   quit                          exit BlastEm            → ok
+
+use super::{Headless, free_port, headless_command, parse_hex_bytes, parse_number};
+use crate::error::{Error, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A connection to a running BlastEm's control socket.
+pub struct Control {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    timeout: Duration,
+}
+
+/// Start BlastEm `exe` headless on `rom` with its control socket on a free port, and connect
+/// to it (retrying until it listens, BlastEm exits, or `timeout` passes). `timeout` also bounds
+/// every later reply, plus the time the frames of a `frames` request take.
+pub fn launch(exe: &Path, rom: &Path, timeout: Duration) -> Result<(Headless, Control)> {
+    let port = free_port()?;
+    let mut cmd = headless_command(exe);
+    cmd.arg(rom).env("BLASTEM_CONTROL_PORT", port.to_string());
+    let mut process = Headless::spawn(cmd)?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => {
+                process.ready();
+                return Ok((process, Control::new(stream, timeout)?));
+            }
+            Err(_) if process.exited() => {
+                process.echo_output();
+                return Err(Error::Emulator(
+                    "BlastEm exited before opening its control socket — is it the \
+                     debug-capable build? (`sgdkx install --only blastem`)"
+                        .to_string(),
+                ));
+            }
+            Err(e) if Instant::now() >= deadline => {
+                return Err(Error::io(
+                    format!(
+                        "BlastEm opened no control socket within {}s — is it a build with the \
+                         control socket (BLASTEM_CONTROL_PORT)?",
+                        timeout.as_secs()
+                    ),
+                    e,
+                ));
+            }
+            Err(_) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+impl Control {
+    fn new(stream: TcpStream, timeout: Duration) -> Result<Control> {
+        let io = |e| Error::io("cannot set up BlastEm's control socket", e);
+        stream.set_read_timeout(Some(timeout)).map_err(io)?;
+        let writer = stream.try_clone().map_err(io)?;
+        Ok(Control {
+            reader: BufReader::new(stream),
+            writer,
+            timeout,
+        })
+    }
+
+    /// Send one request and return the words after `ok`.
+    fn request(&mut self, line: &str) -> Result<Vec<String>> {
+        let io = |e| Error::io(format!("BlastEm control `{line}`"), e);
+        writeln!(self.writer, "{line}").map_err(io)?;
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply).map_err(io)? == 0 {
+            return Err(Error::Emulator(format!("BlastEm closed its control socket on `{line}`")));
+        }
+        let mut words = reply.split_whitespace().map(str::to_string);
+        match words.next().as_deref() {
+            Some("ok") => Ok(words.collect()),
+            Some("error") => Err(Error::Emulator(format!(
+                "BlastEm rejected `{line}`: {}",
+                words.collect::<Vec<_>>().join(" ")
+            ))),
+            _ => Err(bad_reply(line, &reply)),
+        }
+    }
+
+    /// Run `n` frames; returns the frame count reached.
+    pub fn frames(&mut self, n: u64) -> Result<u64> {
+        let line = format!("frames {n}");
+        // headless BlastEm runs well above 60 fps; allow 50 for a slow CI machine
+        let run_time = Duration::from_secs(n / 50);
+        let set_timeout = |stream: &TcpStream, timeout| {
+            stream
+                .set_read_timeout(Some(timeout))
+                .map_err(|e| Error::io("cannot set up BlastEm's control socket", e))
+        };
+        set_timeout(self.reader.get_ref(), self.timeout + run_time)?;
+        let reply = self.request(&line);
+        set_timeout(self.reader.get_ref(), self.timeout)?;
+        number(&line, reply?.first())
+    }
+
+    /// Hold `buttons` (e.g. `A+START`; `none` releases all) on controller `pad` (1 or 2).
+    pub fn input(&mut self, pad: u8, buttons: &str) -> Result<()> {
+        self.request(&format!("input {pad} {buttons}")).map(drop)
+    }
+
+    /// The last rendered frame: `(width, height, RGB888 pixels row by row)`.
+    pub fn framebuffer(&mut self) -> Result<(u32, u32, Vec<u8>)> {
+        let line = "framebuffer";
+        let reply = self.request(line)?;
+        let width = number(line, reply.first())? as u32;
+        let height = number(line, reply.get(1))? as u32;
+        let pixels = parse_hex_bytes(reply.get(2).map_or("", String::as_str))
+            .filter(|p| p.len() == width as usize * height as usize * 3)
+            .ok_or_else(|| bad_reply(line, "framebuffer size mismatch"))?;
+        Ok((width, height, pixels))
+    }
+
+    /// Ask BlastEm to exit (the `Headless` drop kills it if it doesn't).
+    pub fn quit(mut self) {
+        let _ = self.request("quit");
+    }
+}
+
+fn bad_reply(line: &str, reply: &str) -> Error {
+    Error::Emulator(format!("unexpected reply to `{line}`: {}", reply.trim()))
+}
+
+fn number(line: &str, word: Option<&String>) -> Result<u64> {
+    word.and_then(|w| parse_number(w))
+        .ok_or_else(|| bad_reply(line, word.map_or("", String::as_str)))
+}
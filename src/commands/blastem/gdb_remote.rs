@@ -0,0 +1,223 @@
+// Client for the gdb remote serial protocol served by the debug-capable BlastEm
+// (`start_gdb_server`) — the stub m68k-elf-gdb talks to, driven directly so `sgdkx test` needs
+// no gdb. Only the packets the test runner uses:
+//
+//   QStartNoAckMode   stop acknowledging packets     → OK | (empty: unsupported)
+//   ?                 why the target is stopped      → S<sig> | T<sig>...
+//   m<addr>,<len>     read memory                    → <hex bytes> | E<nn>
+//   Z0,<addr>,2       insert a breakpoint            → OK | E<nn> | (empty: unsupported)
+//   z0,<addr>,2       remove it                      → OK | E<nn>
+//   c / s             continue / single-step         → S<sig> | T<sig>... | W<status> (exited)
+//   g                 read registers                 → d0-d7 a0-a7 sr pc, 8 hex digits each
+//   k                 kill                           (no reply)
+//
+// Packets travel as `$<data>#<checksum>` (sum of the data bytes mod 256, two hex digits) and
+// are acknowledged with `+` unless the server agrees to drop the acks (one write per packet
+// each way, instead of an ack write racing the reply); replies may be run-length encoded
+// (`<c>*<n>`).
+
+use super::parse_hex_bytes;
+use crate::error::{Error, Result};
+use std::io::{BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// SIGTRAP: a stop at a breakpoint or after a single step.
+const SIGTRAP: u8 = 5;
+
+/// Index of the PC in the `g` register dump (after d0-d7, a0-a7 and sr).
+const PC_REGISTER: usize = 17;
+
+/// A connection to a gdb server (BlastEm started with `start_gdb_server`), stopped until it is
+/// resumed.
+pub struct Remote {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    acks: bool,
+}
+
+impl Remote {
+    /// Connect to the gdb server on localhost:`port`. `timeout` bounds every reply — a
+    /// continue included, so resume only to points that come around soon (e.g. each frame).
+    pub fn connect(port: u16, timeout: Duration) -> Result<Remote> {
+        let io = |e| Error::io(format!("cannot connect to BlastEm's gdb server on port {port}"), e);
+        let stream = TcpStream::connect(("127.0.0.1", port)).map_err(io)?;
+        stream.set_read_timeout(Some(timeout)).map_err(io)?;
+        stream.set_nodelay(true).map_err(io)?;
+        let writer = stream.try_clone().map_err(io)?;
+        let mut remote = Remote {
+            reader: BufReader::new(stream),
+            writer,
+            acks: true,
+        };
+        if remote.request("QStartNoAckMode")? == "OK" {
+            remote.acks = false;
+        }
+        remote.request("?")?; // the initial stop
+        Ok(remote)
+    }
+
+    /// Send `data` as one packet and return the reply's data.
+    fn request(&mut self, data: &str) -> Result<String> {
+        let io = |e| Error::io(format!("gdb server request `{data}`"), e);
+        self.writer.write_all(packet(data).as_bytes()).map_err(io)?;
+        let reply = self.receive().map_err(io)?;
+        if reply.starts_with('E') && reply.len() == 3 {
+            return Err(Error::Emulator(format!("gdb server rejected `{data}`: {reply}")));
+        }
+        Ok(reply)
+    }
+
+    /// Read the next packet (skipping acks), acknowledge it and return its decoded data.
+    fn receive(&mut self) -> std::io::Result<String> {
+        let mut byte = [0; 1];
+        loop {
+            self.reader.read_exact(&mut byte)?;
+            if byte[0] == b'$' {
+                break;
+            }
+        }
+        let mut data = Vec::new();
+        loop {
+            self.reader.read_exact(&mut byte)?;
+            if byte[0] == b'#' {
+                break;
+            }
+            data.push(byte[0]);
+        }
+        let mut sum = [0; 2];
+        self.reader.read_exact(&mut sum)?;
+        let ok = std::str::from_utf8(&sum)
+            .ok()
+            .and_then(|s| u8::from_str_radix(s, 16).ok())
+            .is_some_and(|s| s == checksum(&data));
+        if !ok && self.acks {
+            self.writer.write_all(b"-")?; // ask for a retransmission
+            return self.receive();
+        }
+        if !ok {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "bad packet checksum"));
+        }
+        if self.acks {
+            self.writer.write_all(b"+")?;
+        }
+        Ok(expand_rle(&String::from_utf8_lossy(&data)))
+    }
+
+    /// `len` bytes of memory at `addr`.
+    pub fn read_memory(&mut self, addr: u32, len: usize) -> Result<Vec<u8>> {
+        let data = format!("m{addr:x},{len:x}");
+        let reply = self.request(&data)?;
+        parse_hex_bytes(&reply)
+            .filter(|b| b.len() == len)
+            .ok_or_else(|| bad_reply(&data, &reply))
+    }
+
+    pub fn insert_breakpoint(&mut self, addr: u32) -> Result<()> {
+        self.expect_ok(&format!("Z0,{addr:x},2"))
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: u32) -> Result<()> {
+        self.expect_ok(&format!("z0,{addr:x},2"))
+    }
+
+    fn expect_ok(&mut self, data: &str) -> Result<()> {
+        match self.request(data)?.as_str() {
+            "OK" => Ok(()),
+            "" => Err(Error::Emulator(format!("gdb server does not support `{data}`"))),
+            reply => Err(bad_reply(data, reply)),
+        }
+    }
+
+    /// Continue until the target stops again.
+    pub fn resume(&mut self) -> Result<()> {
+        self.stopped("c")
+    }
+
+    /// Execute one instruction.
+    pub fn step(&mut self) -> Result<()> {
+        self.stopped("s")
+    }
+
+    /// Send `c`/`s` and check that the target then stopped on SIGTRAP (a breakpoint or the
+    /// step), not on an exception or by exiting.
+    fn stopped(&mut self, data: &str) -> Result<()> {
+        let reply = self.request(data)?;
+        let signal = reply.get(1..3).and_then(|s| u8::from_str_radix(s, 16).ok());
+        match (reply.chars().next(), signal) {
+            (Some('S' | 'T'), Some(SIGTRAP)) => Ok(()),
+            (Some('S' | 'T'), Some(signal)) => {
+                let pc = self.pc().map_or(String::new(), |pc| format!(" at {pc:#x}"));
+                Err(Error::Emulator(format!("the 68k stopped with signal {signal}{pc}")))
+            }
+            (Some('W' | 'X'), _) => Err(Error::Emulator("BlastEm exited".to_string())),
+            _ => Err(bad_reply(data, &reply)),
+        }
+    }
+
+    /// The 68k's program counter.
+    pub fn pc(&mut self) -> Result<u32> {
+        let reply = self.request("g")?;
+        reply
+            .get(PC_REGISTER * 8..PC_REGISTER * 8 + 8)
+            .and_then(|pc| u32::from_str_radix(pc, 16).ok())
+            .ok_or_else(|| bad_reply("g", &reply))
+    }
+
+    /// Stop the session (the `Headless` drop stops BlastEm if it doesn't exit).
+    pub fn kill(mut self) {
+        let _ = self.writer.write_all(packet("k").as_bytes());
+    }
+}
+
+fn bad_reply(data: &str, reply: &str) -> Error {
+    Error::Emulator(format!("unexpected reply to `{data}` from the gdb server: `{reply}`"))
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
+
+/// `data` framed as a packet.
+fn packet(data: &str) -> String {
+    format!("${data}#{:02x}", checksum(data.as_bytes()))
+}
+
+/// Undo run-length encoding: `<c>*<n>` stands for `<c>` followed by `n - 29` more copies
+/// (`n` printable).
+fn expand_rle(data: &str) -> String {
+    let mut out = String::with_capacity(data.len());
+    let mut chars = data.chars();
+    while let Some(c) = chars.next() {
+        if c != '*' {
+            out.push(c);
+            continue;
+        }
+        let (Some(prev), Some(n)) = (out.chars().last(), chars.next()) else {
+            break;
+        };
+        let repeat = (n as usize).saturating_sub(29);
+        out.extend(std::iter::repeat_n(prev, repeat));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_carry_a_mod_256_checksum() {
+        assert_eq!(packet("g"), "$g#67");
+        assert_eq!(packet("m78,4"), "$m78,4#3c");
+        assert_eq!(packet(""), "$#00");
+    }
+
+    #[test]
+    fn run_length_encoding_is_expanded() {
+        // '*' then ' ' (32): 3 more copies; '"' (34): 5 more
+        assert_eq!(expand_rle("0* "), "0000");
+        assert_eq!(expand_rle("00f*\"12"), "00ffffff12");
+        assert_eq!(expand_rle("OK"), "OK");
+    }
+}
@@ -0,0 +1,409 @@
+// `sgdkx capture`: golden-image tests. Boot a ROM in a headless BlastEm (driven through its
+// control socket, see `blastem::control`), optionally replay a scripted input sequence, and
+// write the framebuffer as PNG at the given frames. `--compare` diffs each shot against a
+// reference image, so a changed sprite or palette fails CI instead of going unnoticed.
+
+use crate::commands::blastem::control::{self, Control};
+use crate::commands::blastem::{parse_number, require_blastem};
+use crate::commands::run;
+use crate::error::{Error, OrExit, Result};
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Parser)]
+pub struct Args {
+    /// ROM to capture (default: build the project and use out/rom.bin)
+    rom: Option<PathBuf>,
+
+    /// Frame numbers to capture, comma separated (e.g. 120,300)
+    #[arg(long, value_name = "N,...", value_delimiter = ',', required = true)]
+    frames: Vec<u64>,
+
+    /// Directory the PNGs are written to (frame-<n>.png, zero-padded: frame-0120.png)
+    #[arg(long, value_name = "DIR", default_value = "shots")]
+    out: PathBuf,
+
+    /// Input script: one `<frame> [<pad>:]<buttons>` per line, e.g. `60 START`, `90 2:A+RIGHT`,
+    /// `95 none`; buttons are held until the next line for that pad
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Compare each shot with the same-named PNG in DIR (not --out); exits non-zero on a
+    /// difference, with diff images in <out>/diff
+    #[arg(long, value_name = "DIR")]
+    compare: Option<PathBuf>,
+
+    /// With --compare: how far (0-255) a color channel may differ before the pixel counts as changed
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "compare")]
+    tolerance: u8,
+
+    /// Seconds BlastEm gets to start and to answer each request
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    timeout: u64,
+}
+
+/// The controller buttons an input script may name.
+const BUTTONS: &[&str] = &[
+    "UP", "DOWN", "LEFT", "RIGHT", "A", "B", "C", "X", "Y", "Z", "START", "MODE",
+];
+
+/// What happens at one frame: inputs to apply (pad, buttons), and whether to capture it.
+#[derive(Default)]
+struct Step {
+    inputs: Vec<(u8, String)>,
+    capture: bool,
+}
+
+pub fn run(args: &Args) {
+    let exe = require_blastem(Path::new(".")).or_exit();
+    let mut timeline: BTreeMap<u64, Step> = BTreeMap::new();
+    if let Some(script) = &args.input {
+        for (frame, pad, buttons) in read_input_script(script).or_exit() {
+            timeline.entry(frame).or_default().inputs.push((pad, buttons));
+        }
+    }
+    for &frame in &args.frames {
+        timeline.entry(frame).or_default().capture = true;
+    }
+    if let Some(golden) = &args.compare
+        && same_dir(golden, &args.out)
+    {
+        Error::InvalidArgument(format!(
+            "--out and --compare are both {}: the shots would overwrite the reference images",
+            golden.display()
+        ))
+        .exit();
+    }
+    let rom = match &args.rom {
+        Some(rom) if !rom.is_file() => {
+            Error::InvalidArgument(format!("{} not found", rom.display())).exit()
+        }
+        Some(rom) => rom.clone(),
+        None => run::build_rom(Path::new("."), false, &[]).or_exit(),
+    };
+    fs::create_dir_all(&args.out)
+        .map_err(|e| Error::io(format!("cannot create {}", args.out.display()), e))
+        .or_exit();
+
+    println!("📸 Capturing {} in headless BlastEm...", rom.display());
+    let shots = capture(&exe, &rom, &timeline, &args.out, args.timeout).or_exit();
+    let Some(golden) = &args.compare else {
+        return;
+    };
+
+    let diffs = args.out.join("diff");
+    let mut failed = 0;
+    for shot in &shots {
+        let name = shot.file_name().unwrap_or_default();
+        match compare(shot, &golden.join(name), args.tolerance, &diffs.join(name)) {
+            Ok(None) => println!("✅ {} matches", name.display()),
+            Ok(Some(reason)) => {
+                failed += 1;
+                println!("❌ {}: {reason}", name.display());
+            }
+            Err(e) => {
+                failed += 1;
+                println!("❌ {}: {e}", name.display());
+            }
+        }
+    }
+    if failed > 0 {
+        eprintln!(
+            "❌ {failed} of {} frame(s) differ from {} (accept the new shots with `cp {}/*.png {}/`)",
+            shots.len(),
+            golden.display(),
+            args.out.display(),
+            golden.display()
+        );
+        std::process::exit(1);
+    }
+    println!("✅ All {} frame(s) match {}", shots.len(), golden.display());
+}
+
+/// Run `rom` through `timeline`, writing `frame-<n>.png` (`<n>` zero-padded to 4 digits, e.g.
+/// `frame-0120.png`) into `out` for each capture step.
+/// Returns the PNGs written.
+fn capture(
+    exe: &Path,
+    rom: &Path,
+    timeline: &BTreeMap<u64, Step>,
+    out: &Path,
+    timeout: u64,
+) -> Result<Vec<PathBuf>> {
+    let (_blastem, mut control) = control::launch(exe, rom, Duration::from_secs(timeout))?;
+    let mut shots = Vec::new();
+    let mut frame = 0;
+    for (&at, step) in timeline {
+        if at > frame {
+            control.frames(at - frame)?;
+            frame = at;
+        }
+        for (pad, buttons) in &step.inputs {
+            control.input(*pad, buttons)?;
+        }
+        if step.capture {
+            let shot = out.join(format!("frame-{at:04}.png"));
+            save_framebuffer(&mut control, &shot)?;
+            println!("🖼️  {}", shot.display());
+            shots.push(shot);
+        }
+    }
+    control.quit();
+    Ok(shots)
+}
+
+fn save_framebuffer(control: &mut Control, file: &Path) -> Result<()> {
+    let (width, height, pixels) = control.framebuffer()?;
+    write_png(file, width, height, &pixels)
+}
+
+/// Parse an input script (see `Args::input`) into `(frame, pad, buttons)` entries. Blank lines
+/// and `#` comments are skipped.
+fn read_input_script(file: &Path) -> Result<Vec<(u64, u8, String)>> {
+    let text = fs::read_to_string(file)
+        .map_err(|e| Error::io(format!("cannot read {}", file.display()), e))?;
+    let mut entries = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |why: &str| {
+            Error::InvalidArgument(format!("{}:{}: {why}: `{line}`", file.display(), n + 1))
+        };
+        let (frame, input) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| invalid("expected `<frame> [<pad>:]<buttons>`"))?;
+        let frame = parse_number(frame).ok_or_else(|| invalid("bad frame number"))?;
+        let (pad, buttons) = match input.trim().split_once(':') {
+            Some(("1", buttons)) => (1, buttons),
+            Some(("2", buttons)) => (2, buttons),
+            Some(_) => return Err(invalid("the pad must be 1 or 2")),
+            None => (1, input.trim()),
+        };
+        let buttons = match buttons.to_ascii_uppercase() {
+            none if none == "NONE" => "none".to_string(),
+            held if held.split('+').all(|b| BUTTONS.contains(&b)) => held,
+            _ => {
+                let names = BUTTONS.join(", ");
+                return Err(invalid(&format!("buttons are `none` or {names} joined by +")));
+            }
+        };
+        entries.push((frame, pad, buttons));
+    }
+    Ok(entries)
+}
+
+/// Whether `a` and `b` name the same directory (they may not exist yet).
+fn same_dir(a: &Path, b: &Path) -> bool {
+    let resolve = |p: &Path| {
+        p.canonicalize()
+            .or_else(|_| std::path::absolute(p))
+            .unwrap_or_else(|_| p.to_path_buf())
+    };
+    resolve(a) == resolve(b)
+}
+
+/// Compare `shot` with `golden`: None if every pixel is within `tolerance`, else why not. On a
+/// difference, `diff_file` (kept out of the shots directory, so the shots can be copied over the
+/// references as they are) marks the changed pixels in red.
+fn compare(shot: &Path, golden: &Path, tolerance: u8, diff_file: &Path) -> Result<Option<String>> {
+    if !golden.is_file() {
+        return Ok(Some(format!("no reference image {}", golden.display())));
+    }
+    let (width, height, actual) = read_png(shot)?;
+    let (gw, gh, expected) = read_png(golden)?;
+    if (width, height) != (gw, gh) {
+        return Ok(Some(format!("size {width}x{height}, reference is {gw}x{gh}")));
+    }
+
+    let mut changed = 0;
+    let mut worst = 0;
+    let mut diff = Vec::with_capacity(actual.len());
+    for (a, e) in actual.chunks_exact(3).zip(expected.chunks_exact(3)) {
+        let delta = a.iter().zip(e).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+        worst = worst.max(delta);
+        if delta > tolerance {
+            changed += 1;
+            diff.extend_from_slice(&[255, 0, 0]);
+        } else {
+            // the unchanged image, dimmed to gray so the red stands out
+            let gray = ((e[0] as u16 + e[1] as u16 + e[2] as u16) / 6) as u8;
+            diff.extend_from_slice(&[gray; 3]);
+        }
+    }
+    if changed == 0 {
+        let _ = fs::remove_file(diff_file); // from an earlier, failing run
+        return Ok(None);
+    }
+    if let Some(dir) = diff_file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| Error::io(format!("cannot create {}", dir.display()), e))?;
+    }
+    write_png(diff_file, width, height, &diff)?;
+    Ok(Some(format!(
+        "{changed} pixel(s) differ (max channel difference {worst}, tolerance {tolerance}); see {}",
+        diff_file.display()
+    )))
+}
+
+fn write_png(file: &Path, width: u32, height: u32, rgb: &[u8]) -> Result<()> {
+    let err = |e: png::EncodingError| {
+        Error::io(format!("cannot write {}", file.display()), std::io::Error::other(e))
+    };
+    let out = File::create(file)
+        .map_err(|e| Error::io(format!("cannot create {}", file.display()), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(out), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(err)?;
+    writer.write_image_data(rgb).map_err(err)?;
+    writer.finish().map_err(err)
+}
+
+/// A PNG as `(width, height, RGB888 pixels)`, whatever its color type and depth.
+fn read_png(file: &Path) -> Result<(u32, u32, Vec<u8>)> {
+    let err = |e: png::DecodingError| {
+        Error::io(format!("cannot read {}", file.display()), std::io::Error::other(e))
+    };
+    let input =
+        File::open(file).map_err(|e| Error::io(format!("cannot open {}", file.display()), e))?;
+    let mut decoder = png::Decoder::new(BufReader::new(input));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(err)?;
+    let mut buf = vec![0; reader.output_buffer_size().unwrap_or_default()];
+    let info = reader.next_frame(&mut buf).map_err(err)?;
+    buf.truncate(info.buffer_size());
+    let rgb = match info.color_type {
+        png::ColorType::Rgb => buf,
+        png::ColorType::Rgba => buf.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g; 3]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0]; 3]).collect(),
+        // normalize_to_color8 expands palettes to RGB(A)
+        png::ColorType::Indexed => unreachable!("indexed PNG not expanded"),
+    };
+    Ok((info.width, info.height, rgb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(text: &str) -> Result<Vec<(u64, u8, String)>> {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("input.txt");
+        fs::write(&file, text).unwrap();
+        read_input_script(&file)
+    }
+
+    #[test]
+    fn input_script_lines_name_a_frame_pad_and_buttons() {
+        let entries = script("# title\n\n60 start\n90 2:A+right  # jump\n0x78 none\n").unwrap();
+        assert_eq!(
+            entries,
+            [
+                (60, 1, "START".to_string()),
+                (90, 2, "A+RIGHT".to_string()),
+                (120, 1, "none".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn input_script_errors_name_the_line() {
+        for (text, why) in [
+            ("60 START\n90 JUMP\n", ":2: buttons are"),
+            ("60 3:A\n", ":1: the pad must be 1 or 2"),
+            ("soon A\n", ":1: bad frame number"),
+            ("60\n", ":1: expected"),
+        ] {
+            let err = script(text).unwrap_err().to_string();
+            assert!(err.contains(why), "{text:?}: {err}");
+        }
+    }
+
+    /// Write `pixels` as a `width`x1 PNG of color type `color`.
+    fn png_of(file: &Path, color: png::ColorType, width: u32, pixels: &[u8]) {
+        let out = BufWriter::new(File::create(file).unwrap());
+        let mut encoder = png::Encoder::new(out, width, 1);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+    }
+
+    struct Images {
+        dir: tempfile::TempDir,
+    }
+
+    impl Images {
+        fn new(shot: &[u8]) -> Images {
+            let dir = tempfile::tempdir().unwrap();
+            write_png(&dir.path().join("shot.png"), shot.len() as u32 / 3, 1, shot).unwrap();
+            Images { dir }
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.dir.path().join(name)
+        }
+
+        fn compare(&self, tolerance: u8) -> Option<String> {
+            let (shot, golden) = (self.path("shot.png"), self.path("golden.png"));
+            compare(&shot, &golden, tolerance, &self.path("diff/shot.png")).unwrap()
+        }
+    }
+
+    #[test]
+    fn differences_up_to_the_tolerance_pass() {
+        let images = Images::new(&[100, 100, 100, 0, 0, 0]);
+        png_of(&images.path("golden.png"), png::ColorType::Rgb, 2, &[100, 100, 110, 0, 0, 0]);
+
+        assert_eq!(images.compare(10), None);
+        assert!(!images.path("diff").exists());
+        let failure = images.compare(9).unwrap();
+        assert!(failure.starts_with("1 pixel(s) differ (max channel difference 10"), "{failure}");
+        let (_, _, diff) = read_png(&images.path("diff/shot.png")).unwrap();
+        assert_eq!(&diff[..3], [255, 0, 0]);
+
+        // a later match removes the diff of the failing run
+        images.compare(10);
+        assert!(!images.path("diff/shot.png").exists());
+    }
+
+    #[test]
+    fn rgba_and_grayscale_references_compare_as_rgb() {
+        let images = Images::new(&[10, 20, 30, 50, 50, 50]);
+        let golden = images.path("golden.png");
+        png_of(&golden, png::ColorType::Rgba, 2, &[10, 20, 30, 255, 50, 50, 50, 0]);
+        assert_eq!(images.compare(0), None);
+
+        png_of(&golden, png::ColorType::Grayscale, 2, &[20, 50]);
+        assert_eq!(images.compare(10), None);
+        assert!(images.compare(9).is_some());
+    }
+
+    #[test]
+    fn size_mismatch_and_missing_reference_fail() {
+        let images = Images::new(&[0; 6]);
+        let failure = images.compare(0).unwrap();
+        assert!(failure.starts_with("no reference image"), "{failure}");
+
+        png_of(&images.path("golden.png"), png::ColorType::Rgb, 1, &[0; 3]);
+        assert_eq!(images.compare(255).unwrap(), "size 2x1, reference is 1x1");
+    }
+
+    #[test]
+    fn out_and_compare_may_not_be_the_same_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let golden = dir.path().join("golden");
+        fs::create_dir(&golden).unwrap();
+        assert!(same_dir(&golden, &golden.join("../golden")));
+        assert!(same_dir(&golden, &golden.join(".")));
+        assert!(!same_dir(&golden, &dir.path().join("shots")));
+    }
+}
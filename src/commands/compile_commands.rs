@@ -1,4 +1,5 @@
 use crate::commands::new::generate_compile_commands;
+use crate::error::OrExit;
 use crate::path;
 use clap::Parser;
 use std::path::Path;
@@ -29,5 +30,6 @@ pub fn run(args: &Args) {
         );
         std::process::exit(1);
     }
+    crate::commands::make::require_build_pins(project).or_exit();
     generate_compile_commands(project);
 }
@@ -0,0 +1,75 @@
+// `sgdkx debug`: source-level debugging from any terminal or editor — what the generated VS Code
+// tasks.json + launch.json do, natively. Build the -O0 debug ROM, start BlastEm headless as a gdb
+// server on a free port, wait until it listens, then run m68k-elf-gdb connected to it (stopped
+// at `main`). BlastEm is shut down when gdb exits.
+
+use crate::commands::blastem::{self, require_blastem};
+use crate::commands::gdb::find_gdb;
+use crate::commands::{make, run};
+use crate::error::{Error, OrExit};
+use crate::path;
+use clap::Parser;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Also step into SGDK source: build with SGDK_DEBUG=1 (libmd_debug.a; needs SGDK >= 2.10)
+    #[arg(long = "sgdk-source")]
+    sgdk_source: bool,
+
+    /// Arguments passed to m68k-elf-gdb (after `--`, e.g. `sgdkx debug -- -x breakpoints.gdb`)
+    #[arg(last = true)]
+    gdb_args: Vec<String>,
+}
+
+/// Where sgdk-native-builds compiles SGDK in CI: the source path baked into libmd_debug.a's
+/// debug info, remapped to the local install so gdb finds SGDK's sources (here, and in the
+/// sourceFileMap `sgdkx new` writes into launch.json).
+pub const SGDK_BUILD_DIR: &str = "/Users/runner/work/sgdk-native-builds/sgdk-native-builds/SGDK";
+
+pub fn run(args: &Args) {
+    let project = Path::new(".");
+    let blastem = require_blastem(project).or_exit();
+    let config_dir = path::config_dir();
+    let gdb = find_gdb(&config_dir)
+        .ok_or(Error::ToolNotFound("m68k-elf-gdb"))
+        .or_exit();
+
+    // switching OPT / SGDK_DEBUG needs a clean debug build (make keys on timestamps)
+    let cleaned = make::build(project, &["clean-debug"]).or_exit();
+    if !cleaned.success() {
+        Error::BuildFailed("make clean-debug failed".to_string()).exit();
+    }
+    let mut make_args = vec!["OPT=-O0"];
+    if args.sgdk_source {
+        make_args.push("SGDK_DEBUG=1");
+    }
+    let rom = run::build_rom(project, true, &make_args).or_exit();
+
+    println!("🐞 Starting BlastEm as a gdb server...");
+    let server =
+        blastem::start_gdb_server(&blastem, &rom, &[], None, true, blastem::READY_TIMEOUT).or_exit();
+    let port = server.port;
+    println!("🐞 BlastEm waiting for gdb on localhost:{port}");
+
+    // ^C belongs to gdb (it interrupts the target); sgdkx must outlive it to stop BlastEm
+    let _ = ctrlc::set_handler(|| {});
+    let sgdk = path::sgdk_dir_for(project).or_exit().to_string_lossy().replace('\\', "/");
+    let status = Command::new(&gdb)
+        .arg("-q")
+        .arg(rom.with_extension("out"))
+        .arg("-ex")
+        .arg(format!("set substitute-path {SGDK_BUILD_DIR} \"{sgdk}\""))
+        .arg("-ex")
+        .arg(format!("target remote localhost:{port}"))
+        .args(&args.gdb_args)
+        .args(["-ex", "break main", "-ex", "continue"])
+        .status();
+
+    drop(server); // stops BlastEm
+    match status {
+        Ok(s) => std::process::exit(s.code().unwrap_or(1)),
+        Err(e) => Error::io("failed to run m68k-elf-gdb", e).exit(),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::commands::install;
+use crate::error::OrExit;
+use crate::path;
+use clap::Parser;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Installed SGDK version to make the default (see `sgdkx list`). Omitted → print the
+    /// current default.
+    version: Option<String>,
+}
+
+/// Show or switch the default SGDK version (used outside projects, and by projects without an
+/// sgdkx.toml pin). Switching is instant: the version must already be installed.
+pub fn run(args: &Args) {
+    let Some(tag) = args.version.as_deref() else {
+        match path::installed_version() {
+            Some(v) => println!("{v}"),
+            None => println!("No default SGDK. Please run `sgdkx install`."),
+        }
+        return;
+    };
+    let installed = path::installed_sgdk_versions();
+    if !installed.iter().any(|v| v == tag) {
+        eprintln!("❌ SGDK {tag} is not installed. Run `sgdkx install --sgdk {tag}` first.");
+        if !installed.is_empty() {
+            eprintln!("Installed versions:");
+            for v in &installed {
+                eprintln!("  {v}");
+            }
+        }
+        std::process::exit(1);
+    }
+    install::set_default_sgdk(&path::config_dir(), tag).or_exit();
+}
@@ -1,10 +1,11 @@
+use crate::error::OrExit;
 use crate::path;
 use which::which;
 
 /// SGDKドキュメントの表示
 pub fn run() {
-    // config_dirがResultを返すので、unwrap_or_elseでエラー時のデフォルト値を設定
-    let sgdk_path = path::config_dir().join("SGDK");
+    // 有効なSGDK（プロジェクトのピン、なければデフォルト）のドキュメント
+    let sgdk_path = path::sgdk_dir().or_exit();
     let out_html = sgdk_path.join("doc").join("html");
     let index_html = out_html.join("index.html");
 
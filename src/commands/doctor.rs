@@ -1,76 +1,648 @@
+// Environment check. Every component gets a `Check` (status ok / missing / broken, path,
+// version); the same report is printed for humans (the no-arg `sgdkx` view) or as JSON
+// (`sgdkx doctor --json`) for CI, and `sgdkx doctor` exits non-zero when a required
+// component isn't usable. `--deep` goes further and actually runs each tool (and builds a
+// tiny ROM), catching installs whose files exist but don't execute; `--fix` re-fetches just the
+// components that aren't ok.
+
+use crate::error::OrExit;
 use crate::path;
+use crate::release;
+use clap::Parser;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
-pub fn run() {
-    println!("\n🩺 sgdkx v{}", env!("CARGO_PKG_VERSION"));
+#[derive(Parser)]
+pub struct Args {
+    /// Print the report as JSON (for CI / bug reports)
+    #[arg(long)]
+    json: bool,
+
+    /// Also run each tool (gcc, java, gdb, rescomp) and build a test ROM
+    #[arg(long)]
+    deep: bool,
+
+    /// Re-fetch only the missing or broken components (no full reinstall)
+    #[arg(long, conflicts_with = "json")]
+    fix: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Status {
+    Ok,
+    /// Not installed.
+    Missing,
+    /// Present on disk but unusable (e.g. a half-extracted directory).
+    Broken,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Missing => "missing",
+            Status::Broken => "broken",
+        }
+    }
+}
+
+/// The state of one component.
+pub struct Check {
+    /// Stable identifier (the JSON `name`).
+    pub name: &'static str,
+    /// Label in the human report.
+    label: &'static str,
+    /// Whether building a project needs it (a required component that isn't ok fails doctor).
+    pub required: bool,
+    pub status: Status,
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+    /// Extra context: where it comes from (e.g. "bundled" / "system"), or why it's broken.
+    pub detail: Option<String>,
+    /// What config.toml records about the install (repo, asset, checksum, date), if anything.
+    pub source: Option<path::InstallRecord>,
+}
+
+impl Check {
+    fn new(name: &'static str, label: &'static str, required: bool) -> Check {
+        Check {
+            name,
+            label,
+            required,
+            status: Status::Missing,
+            path: None,
+            version: None,
+            detail: None,
+            source: None,
+        }
+    }
+
+    fn ok(mut self, path: PathBuf) -> Check {
+        self.status = Status::Ok;
+        self.path = Some(path);
+        self
+    }
+
+    fn broken(mut self, path: PathBuf, why: &str) -> Check {
+        self.status = Status::Broken;
+        self.path = Some(path);
+        self.detail = Some(why.to_string());
+        self
+    }
+
+    fn version(mut self, version: Option<String>) -> Check {
+        self.version = version;
+        self
+    }
+
+    /// Attach config.toml's record under `key`; its tag is the version unless one is already set.
+    fn source(mut self, key: &str) -> Check {
+        self.source = path::install_record(key);
+        if self.version.is_none() {
+            self.version = self.source.as_ref().map(|r| r.version.clone());
+        }
+        self
+    }
+
+    fn detail(mut self, detail: &str) -> Check {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    /// A required component that isn't usable.
+    pub fn fails(&self) -> bool {
+        self.required && self.status != Status::Ok
+    }
+}
+
+pub fn run(args: &Args) {
+    if args.fix {
+        fix(&report());
+    }
+    let checks = report();
+    let steps = if args.deep { deep_checks(&checks) } else { Vec::new() };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&checks, &steps)).unwrap());
+    } else {
+        print_report(&checks);
+        if args.deep {
+            print_steps(&steps);
+        }
+    }
+    if checks.iter().any(Check::fails) || steps.iter().any(Step::fails) {
+        if !args.json {
+            eprintln!("\n❌ A required component is missing or broken. Run `sgdkx install`.");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Re-fetch the components that aren't ok through install's per-component installers, leaving
+/// the healthy ones (and the other SGDK versions) alone.
+fn fix(checks: &[Check]) {
+    let todo: Vec<&Check> = checks.iter().filter(|c| needs_fix(c)).collect();
+    if todo.is_empty() {
+        println!("✅ Nothing to fix.");
+        return;
+    }
+    let config_dir = path::config_dir();
+    for c in todo {
+        if c.name == "make" {
+            println!("⚠️  make: install it with your package manager (e.g. `brew install make`, `apt install make`)");
+            continue;
+        }
+        // an earlier repair may have covered it (the SGDK bundle brings its docs)
+        let still_needed = report()
+            .iter()
+            .any(|now| now.name == c.name && needs_fix(now));
+        if !still_needed {
+            continue;
+        }
+        println!("🔧 Repairing {}...", c.name);
+        match crate::commands::install::repair(&config_dir, c.name) {
+            Ok(()) => println!("✅ {} repaired", c.name),
+            Err(e) => eprintln!("❌ {e}"),
+        }
+    }
+}
+
+/// Missing or broken — or, for Java, only the system one in use where install bundles a JRE.
+fn needs_fix(c: &Check) -> bool {
+    c.status != Status::Ok || (c.name == "jre" && c.detail.as_deref() != Some("bundled"))
+}
+
+/// The no-arg `sgdkx` view: the human report, without failing the process.
+pub fn overview() {
+    print_report(&report());
+}
+
+/// Check every component of the environment.
+pub fn report() -> Vec<Check> {
+    let config_dir = path::config_dir();
+    let mut checks = Vec::new();
 
     // On Unix, `make` is the system one (required). On Windows, make (and the whole
     // toolchain + MSYS shell) is bundled in SGDK/bin and used via `sgdkx make`, so no
     // system tool is required there.
     #[cfg(not(target_os = "windows"))]
-    check_tool("make");
-
-    if path::is_installed() {
-        let sgdk_dir = path::sgdk_dir();
-        let config_base = path::config_dir();
+    checks.push(match which::which("make") {
+        Ok(p) => Check::new("make", "make", true).ok(p).detail("system"),
+        Err(_) => Check::new("make", "make", true),
+    });
 
-        println!("\n📝 sgdkx install: {}", config_base.display());
-        println!("SGDK Path     : {}", sgdk_dir.display());
-        println!(
-            "Version       : {}",
-            path::installed_version().unwrap_or_else(|| "Unknown".to_string())
-        );
+    let sgdk_dir = path::sgdk_dir().or_exit();
+    let active = path::active_sgdk_version(Path::new("."));
+    let sgdk = Check::new("sgdk", "SGDK Path", true).version(active.clone());
+    let sgdk = match &active {
+        Some(tag) => sgdk.source(&format!("sdk.{tag}")),
+        None => sgdk,
+    };
+    checks.push(if path::is_installed() {
+        if sgdk_dir.join("makefile.gen").exists() {
+            sgdk.ok(sgdk_dir.clone())
+        } else {
+            sgdk.broken(sgdk_dir.clone(), "makefile.gen missing")
+        }
+    } else if sgdk_dir.exists() {
+        sgdk.broken(sgdk_dir.clone(), "bin/ missing")
+    } else {
+        sgdk
+    });
 
-        match path::toolchain_dir() {
-            Some(tc) => println!("Toolchain     : {}", tc.display()),
-            None => println!("Toolchain     : bundled (Windows)"),
+    // gcc: a separate component on Unix, inside the SGDK bundle's bin/ on Windows
+    let toolchain = Check::new("toolchain", "Toolchain", true).source("toolchain");
+    checks.push(if cfg!(target_os = "windows") {
+        toolchain.ok(sgdk_dir.join("bin")).detail("bundled (Windows)")
+    } else {
+        let dir = config_dir.join("m68k-elf-toolchain");
+        if dir.join("bin").join("m68k-elf-gcc").exists() {
+            toolchain.ok(dir)
+        } else if dir.exists() {
+            toolchain.broken(dir, "bin/m68k-elf-gcc missing")
+        } else {
+            toolchain
         }
+    });
 
-        // Bundled Java runtime (used by make for rescomp/sizebnd); falls back to
-        // system Java only if absent.
-        match path::jre_dir() {
-            Some(j) => println!("JRE (bundled) : {}", j.display()),
-            None => match which::which("java") {
-                Ok(p) => println!("JRE           : system java ({})", p.display()),
-                Err(_) => println!("JRE           : ❌ none (no bundled JRE, no system java)"),
-            },
+    // Bundled Java runtime (used by make for rescomp/sizebnd); falls back to system Java only
+    // if absent.
+    let jre = Check::new("jre", "JRE", true).source("jre");
+    let jre_dir = config_dir.join("jre");
+    checks.push(match path::jre_dir() {
+        Some(j) => jre.ok(j).detail("bundled"),
+        None => match which::which("java") {
+            Ok(p) if jre_dir.exists() => jre.ok(p).detail("system (bundled JRE is broken)"),
+            Ok(p) => jre.ok(p).detail("system"),
+            Err(_) if jre_dir.exists() => jre.broken(jre_dir, "bin/ missing"),
+            Err(_) => jre,
+        },
+    });
+
+    // BlastEm (the only supported emulator) — located by search under <config>/blastem.
+    let blastem = Check::new("blastem", "BlastEm", false).source("blastem");
+    checks.push(match crate::commands::blastem::find_blastem(&config_dir) {
+        Some(p) => blastem.ok(p),
+        None if config_dir.join("blastem").exists() => {
+            blastem.broken(config_dir.join("blastem"), "executable not found")
         }
+        None => blastem,
+    });
 
-        // BlastEm (the only supported emulator) — located by search under <config>/blastem.
-        match crate::commands::blastem::find_blastem(&config_base) {
-            Some(p) => println!("BlastEm       : {}", p.display()),
-            None => println!("BlastEm       : Not installed"),
+    // m68k-elf-gdb (Unix: downloaded by install; Windows: gdb.exe in the SGDK bundle)
+    let gdb = Check::new("gdb", "GDB", false);
+    checks.push(match crate::commands::gdb::find_gdb(&config_dir) {
+        Some(p) => gdb
+            .ok(p)
+            .version(Some(release::GDB_VERSION.to_string()))
+            .source("gdb"),
+        None if config_dir.join("m68k-elf-gdb").exists() => {
+            gdb.broken(config_dir.join("m68k-elf-gdb"), "bin/m68k-elf-gdb missing")
         }
+        None => gdb,
+    });
+
+    // SGDK documentation
+    let docs = match &active {
+        Some(tag) => Check::new("docs", "Documentation", false).source(&format!("docs.{tag}")),
+        None => Check::new("docs", "Documentation", false),
+    };
+    let doc_index = sgdk_dir.join("doc").join("html").join("index.html");
+    checks.push(if doc_index.exists() {
+        docs.ok(doc_index)
+    } else if sgdk_dir.join("doc").exists() {
+        docs.broken(sgdk_dir.join("doc"), "html/index.html missing")
+    } else {
+        docs
+    });
+
+    checks
+}
+
+/// The report as JSON: `{ sgdkx_version, sgdkx_update, config_dir, ok, components: [...],
+/// deep: [...] }` (`sgdkx_update`: a newer release tag, when the update check is opted in)
+/// (`deep` only with `--deep`).
+pub fn to_json(checks: &[Check], steps: &[Step]) -> serde_json::Value {
+    let components: Vec<_> = checks
+        .iter()
+        .map(|c| {
+            json!({
+                "name": c.name,
+                "status": c.status.as_str(),
+                "required": c.required,
+                "path": c.path.as_ref().map(|p| p.display().to_string()),
+                "version": c.version,
+                "detail": c.detail,
+                "source": c.source.as_ref().map(|r| json!({
+                    "tag": r.version,
+                    "repo": r.repo,
+                    "asset": r.asset,
+                    "sha256": r.sha256,
+                    "installed": r.installed,
+                })),
+            })
+        })
+        .collect();
+    let mut report = json!({
+        "sgdkx_version": env!("CARGO_PKG_VERSION"),
+        "config_dir": path::config_dir().display().to_string(),
+        "sgdk_versions": path::installed_sgdk_versions(),
+        "ok": !checks.iter().any(Check::fails) && !steps.iter().any(Step::fails),
+        "components": components,
+        "sgdkx_update": crate::commands::self_update::newer_release(),
+        "network": network_json(),
+    });
+    if !steps.is_empty() {
+        report["deep"] = steps
+            .iter()
+            .map(|s| {
+                json!({
+                    "name": s.name,
+                    "command": s.command,
+                    "status": s.status.as_str(),
+                    "required": s.required,
+                    "exit_code": s.exit_code,
+                    "output": s.output,
+                    "stderr": s.stderr,
+                })
+            })
+            .collect();
+    }
+    report
+}
+
+/// The effective network settings (mirror, proxy, CA bundle, timeouts, GitHub token / cache).
+fn network_json() -> serde_json::Value {
+    let net = release::network_settings();
+    json!({
+        "mirror": release::mirror(),
+        "proxy": release::effective_proxy(&net).map(|(url, source)| json!({
+            "url": url,
+            "source": source,
+        })),
+        "no_proxy": net.no_proxy,
+        "ca_bundle": net.ca_bundle.map(|p| p.display().to_string()),
+        "connect_timeout_secs": net.connect_timeout,
+        "read_timeout_secs": net.read_timeout,
+        "github_token": release::github_token().is_some(),
+        "cache_ttl_secs": release::cache_ttl(),
+    })
+}
+
+/// The human report's network line: only what differs from a plain direct connection.
+fn describe_network() -> String {
+    let net = release::network_settings();
+    let mut parts = Vec::new();
+    if let Some(m) = release::mirror() {
+        parts.push(format!("mirror {m}"));
+    }
+    if let Some((url, source)) = release::effective_proxy(&net) {
+        parts.push(format!("proxy {url} ({source})"));
+    }
+    if let Some(ca) = &net.ca_bundle {
+        parts.push(format!("CA bundle {}", ca.display()));
+    }
+    if release::github_token().is_some() {
+        parts.push("GitHub token".to_string());
+    }
+    parts.push(format!(
+        "timeouts {}s connect / {}s read",
+        net.connect_timeout, net.read_timeout
+    ));
+    parts.join(", ")
+}
 
-        // m68k-elf-gdb (Unix: downloaded by install; Windows: gdb.exe in the SGDK bundle)
-        match crate::commands::gdb::find_gdb(&config_base) {
-            Some(p) => println!("GDB           : {}", p.display()),
-            None => println!("GDB           : Not installed"),
+fn print_report(checks: &[Check]) {
+    println!("\n🩺 sgdkx v{}", env!("CARGO_PKG_VERSION"));
+    if let Some(tag) = crate::commands::self_update::newer_release() {
+        let command = crate::commands::self_update::update_command();
+        println!("⬆️  sgdkx {tag} is available — run `{command}`");
+    }
+    let find = |name: &str| checks.iter().find(|c| c.name == name);
+
+    if let Some(make) = find("make") {
+        match &make.path {
+            Some(p) => println!("✅ make: {}", p.display()),
+            None => println!("❌ make: not found"),
         }
+    }
 
-        // SGDK documentation
-        let doc_index = sgdk_dir.join("doc").join("html").join("index.html");
-        if doc_index.exists() {
+    let sgdk = find("sgdk").expect("sgdk is always checked");
+    if sgdk.status == Status::Missing {
+        println!("\n❌ Not installed. Please run `sgdkx install`.");
+        return;
+    }
+    println!("\n📝 sgdkx install: {}", path::config_dir().display());
+    for c in checks.iter().filter(|c| !matches!(c.name, "make" | "docs")) {
+        let label = match (c.name, c.detail.as_deref()) {
+            ("jre", Some("bundled")) => "JRE (bundled)".to_string(),
+            _ => c.label.to_string(),
+        };
+        println!("{label:<14}: {}", describe(c));
+        if c.name == "sgdk" {
             println!(
-                "\n📄 SGDK documentation: {}",
-                doc_index
-                    .canonicalize()
-                    .expect("Failed to canonicalize path")
-                    .to_str()
-                    .unwrap()
-                    .replace(r"\\?\", "")
+                "{:<14}: {}",
+                "Version",
+                c.version.as_deref().unwrap_or("Unknown")
             );
-        } else {
-            println!("⚠️  SGDK documentation not found.");
+            let versions = path::installed_sgdk_versions();
+            if versions.len() > 1 {
+                println!("{:<14}: {} (`sgdkx list`)", "Installed", versions.join(", "));
+            }
         }
+    }
+    println!("{:<14}: {}", "Network", describe_network());
+
+    let docs = find("docs").expect("docs are always checked");
+    match (&docs.status, &docs.path) {
+        (Status::Ok, Some(index)) => println!(
+            "\n📄 SGDK documentation: {}",
+            index
+                .canonicalize()
+                .unwrap_or_else(|_| index.clone())
+                .display()
+                .to_string()
+                .replace(r"\\?\", "")
+        ),
+        _ => println!("⚠️  SGDK documentation not found."),
+    }
+}
+
+/// One component's value column in the human report.
+fn describe(c: &Check) -> String {
+    let path = c.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+    match (c.status, c.name) {
+        (Status::Ok, "jre") if c.detail.as_deref() != Some("bundled") => {
+            format!("{} java ({path})", c.detail.as_deref().unwrap_or("system"))
+        }
+        (Status::Ok, "toolchain") if cfg!(target_os = "windows") => "bundled (Windows)".into(),
+        (Status::Ok, _) => path,
+        (Status::Broken, _) => format!(
+            "❌ broken: {} ({path})",
+            c.detail.as_deref().unwrap_or("incomplete")
+        ),
+        (Status::Missing, "jre") => "❌ none (no bundled JRE, no system java)".into(),
+        (Status::Missing, _) if c.required => "❌ Not installed".into(),
+        (Status::Missing, _) => "Not installed".into(),
+    }
+}
+
+// --- deep checks ---
+// Files on disk prove little: a toolchain missing a shared library, or a JRE built for the
+// wrong architecture, only shows up when run. Each step executes one tool with the same PATH /
+// GDK `sgdkx make` uses and keeps its stderr for the report.
+
+/// One `--deep` step: a tool actually executed.
+pub struct Step {
+    pub name: &'static str,
+    /// The command line run (for the report).
+    pub command: String,
+    pub required: bool,
+    /// `Missing` = skipped (the component isn't installed); `Broken` = it ran and failed.
+    pub status: Status,
+    pub exit_code: Option<i32>,
+    /// First line of output (e.g. the version banner).
+    pub output: Option<String>,
+    pub stderr: String,
+}
+
+impl Step {
+    fn skipped(name: &'static str, required: bool, why: &str) -> Step {
+        Step {
+            name,
+            command: String::new(),
+            required,
+            status: Status::Missing,
+            exit_code: None,
+            output: None,
+            stderr: why.to_string(),
+        }
+    }
+
+    /// A required tool that ran and failed (a skipped step is already reported as a missing
+    /// component).
+    pub fn fails(&self) -> bool {
+        self.required && self.status == Status::Broken
+    }
+}
+
+/// Run each tool: `m68k-elf-gcc --version` (SGDK's `bin/gcc` on Windows), `java -version`,
+/// `m68k-elf-gdb --version`, `rescomp` (no args), then compile + link a tiny ROM. Needs a usable SGDK (for PATH/GDK).
+pub fn deep_checks(checks: &[Check]) -> Vec<Step> {
+    let names = ["gcc", "java", "gdb", "rescomp", "rom"];
+    if checks.iter().any(|c| c.name == "sgdk" && c.status != Status::Ok) {
+        return names
+            .into_iter()
+            .map(|n| Step::skipped(n, n != "gdb", "SGDK is not installed"))
+            .collect();
+    }
+    if let Err(e) = crate::commands::make::prepend_tool_path(Path::new(".")) {
+        return names
+            .into_iter()
+            .map(|n| Step::skipped(n, n != "gdb", &e.to_string()))
+            .collect();
+    }
+
+    let bin = path::sgdk_dir().or_exit().join("bin");
+    // the compiler the build uses: the toolchain's m68k-elf-gcc on Unix, SGDK's own `$(BIN)/gcc`
+    // on Windows (no separate toolchain there)
+    let mut gcc = if cfg!(target_os = "windows") {
+        Command::new(bin.join("gcc.exe"))
     } else {
-        println!("\n❌ Not installed. Please run `sgdkx install`.");
+        Command::new("m68k-elf-gcc")
+    };
+    let mut steps = vec![
+        exec("gcc", true, gcc.arg("--version"), |o| o.status.success()),
+        // `java -version` prints its banner on stderr
+        exec("java", true, Command::new("java").arg("-version"), |o| o.status.success()),
+    ];
+    steps.push(match crate::commands::gdb::find_gdb(&path::config_dir()) {
+        Some(gdb) => exec("gdb", false, Command::new(gdb).arg("--version"), |o| {
+            o.status.success()
+        }),
+        None => Step::skipped("gdb", false, "m68k-elf-gdb is not installed"),
+    });
+    // rescomp: a native binary/wrapper in SGDK's bin/, or the classic rescomp.jar run by java.
+    // Without args it prints its usage (and may exit non-zero); only a tool that can't start
+    // at all — e.g. a wrapper failing to find/execute java (126/127) — is a failure.
+    let exe = if cfg!(target_os = "windows") { "rescomp.exe" } else { "rescomp" };
+    let rescomp = if bin.join(exe).exists() {
+        Some(Command::new(bin.join(exe)))
+    } else if bin.join("rescomp.jar").exists() {
+        let mut c = Command::new("java");
+        c.arg("-jar").arg(bin.join("rescomp.jar"));
+        Some(c)
+    } else {
+        None
+    };
+    steps.push(match rescomp {
+        Some(mut cmd) => exec("rescomp", true, &mut cmd, |o| {
+            !matches!(o.status.code(), None | Some(126) | Some(127))
+        }),
+        None => Step::skipped("rescomp", true, "no rescomp in SGDK bin/"),
+    });
+    steps.push(build_test_rom());
+    steps
+}
+
+/// Run `cmd` (stdin closed, output captured) and judge it with `ok`.
+fn exec(
+    name: &'static str,
+    required: bool,
+    cmd: &mut Command,
+    ok: impl Fn(&std::process::Output) -> bool,
+) -> Step {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let command = std::iter::once(program)
+        .chain(cmd.get_args().map(|a| a.to_string_lossy().into_owned()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut step = Step {
+        name,
+        command,
+        required,
+        status: Status::Broken,
+        exit_code: None,
+        output: None,
+        stderr: String::new(),
+    };
+    match cmd.stdin(Stdio::null()).output() {
+        Ok(out) => {
+            let stdout = String::from_utf8_lossy(&out.stdout);
+            step.stderr = String::from_utf8_lossy(&out.stderr).into_owned();
+            step.exit_code = out.status.code();
+            step.output = stdout
+                .lines()
+                .chain(step.stderr.lines())
+                .find(|l| !l.trim().is_empty())
+                .map(|l| l.trim().to_string());
+            if ok(&out) {
+                step.status = Status::Ok;
+            }
+        }
+        Err(e) => step.stderr = format!("failed to run: {e}"),
     }
+    step
 }
 
-#[cfg(not(target_os = "windows"))] // only the Unix branch checks a system tool (`make`)
-fn check_tool(tool: &str) {
-    match which::which(tool) {
-        Ok(path) => println!("✅ {}: {}", tool, path.display()),
-        Err(_) => println!("❌ {}: not found", tool),
+/// Compile + link a one-file project against the active SGDK (`make -f $GDK/makefile.gen`) in
+/// a temp dir, expecting `out/rom.bin`.
+fn build_test_rom() -> Step {
+    const MAIN_C: &str = "#include <genesis.h>\n\n\
+        int main(bool hardReset)\n{\n    (void) hardReset;\n    VDP_drawText(\"sgdkx\", 1, 1);\n\
+        while (TRUE) SYS_doVBlankProcess();\n    return 0;\n}\n";
+    let dir = match tempfile::Builder::new().prefix("sgdkx-doctor-").tempdir() {
+        Ok(d) => d,
+        Err(e) => return Step::skipped("rom", true, &format!("no temp dir: {e}")),
+    };
+    if let Err(e) = std::fs::create_dir_all(dir.path().join("src"))
+        .and_then(|_| std::fs::write(dir.path().join("src").join("main.c"), MAIN_C))
+    {
+        return Step::skipped("rom", true, &format!("cannot write test project: {e}"));
+    }
+    // prepend_tool_path exported GDK (forward-slashed, as MSYS make wants)
+    let gdk = std::env::var("GDK").unwrap_or_default();
+    let makefile = format!("{gdk}/makefile.gen");
+    let rom = dir.path().join("out").join("rom.bin");
+    let mut make = match crate::commands::make::make_command(dir.path(), &["-f", &makefile]) {
+        Ok(c) => c,
+        Err(e) => return Step::skipped("rom", true, &e.to_string()),
+    };
+    let mut step = exec("rom", true, &mut make, |o| o.status.success());
+    step.command = format!("make -f {makefile} (test ROM)");
+    if step.status == Status::Ok && !rom.exists() {
+        step.status = Status::Broken;
+        step.stderr.push_str("make succeeded but out/rom.bin was not produced\n");
+    }
+    if step.status == Status::Ok {
+        step.output = Some(format!(
+            "out/rom.bin ({} bytes)",
+            std::fs::metadata(&rom).map(|m| m.len()).unwrap_or(0)
+        ));
+    }
+    step
+}
+
+fn print_steps(steps: &[Step]) {
+    println!("\n🔬 Deep checks:");
+    for s in steps {
+        match s.status {
+            Status::Ok => println!(
+                "✅ {}: {}",
+                s.command,
+                s.output.as_deref().unwrap_or("ok")
+            ),
+            Status::Missing => println!("⏭️  {}: skipped ({})", s.name, s.stderr.trim()),
+            Status::Broken => {
+                let code = s.exit_code.map_or("no exit code".to_string(), |c| format!("exit {c}"));
+                let mark = if s.required { "❌" } else { "⚠️ " };
+                println!("{mark} {}: failed ({code})", s.command);
+                // the tail of stderr is where compilers and loaders put the actual error
+                let lines: Vec<&str> = s.stderr.lines().collect();
+                for line in &lines[lines.len().saturating_sub(20)..] {
+                    println!("   {line}");
+                }
+            }
+        }
     }
 }
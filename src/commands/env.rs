@@ -0,0 +1,85 @@
+// `sgdkx env`: print the build environment `sgdkx make` sets up (the tool directories to put in
+// front of PATH, and GDK) as shell commands, so Makefiles, IDEs and direnv can use the bundled
+// toolchain without the wrapper — e.g. `eval "$(sgdkx env)"` or `sgdkx env --shell fish | source`.
+
+use crate::commands::make;
+use crate::error::OrExit;
+use clap::Parser;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+pub struct Args {
+    /// Shell syntax to print: bash, zsh, fish, powershell or cmd. Defaults to PowerShell on
+    /// Windows, else fish if $SHELL is fish, else bash/zsh.
+    #[arg(long = "shell", value_parser = clap::builder::PossibleValuesParser::new(SHELLS))]
+    shell: Option<String>,
+
+    /// Print `{ "gdk": ..., "path": [...] }` (the directories to prepend to PATH) instead
+    #[arg(long, conflicts_with = "shell")]
+    json: bool,
+}
+
+const SHELLS: [&str; 5] = ["bash", "zsh", "fish", "powershell", "cmd"];
+
+/// Print the environment for the project in the current directory (its pinned SGDK, else the
+/// default).
+pub fn run(args: &Args) {
+    let project = Path::new(".");
+    make::require_build_pins(project).or_exit();
+    let env = make::tool_env(project).or_exit();
+    if args.json {
+        let path: Vec<String> = env.path.iter().map(|p| p.display().to_string()).collect();
+        let report = json!({ "gdk": env.gdk, "path": path });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return;
+    }
+    let shell = args.shell.clone().unwrap_or_else(default_shell);
+    print!("{}", script(&shell, &env.path, &env.gdk));
+}
+
+fn default_shell() -> String {
+    if cfg!(target_os = "windows") {
+        return "powershell".to_string();
+    }
+    let fish = std::env::var("SHELL").is_ok_and(|s| s.ends_with("/fish"));
+    if fish { "fish" } else { "bash" }.to_string()
+}
+
+/// The commands setting PATH (`path` in front of the current value) and GDK in `shell`'s syntax.
+fn script(shell: &str, path: &[PathBuf], gdk: &str) -> String {
+    let dirs: Vec<String> = path.iter().map(|p| p.display().to_string()).collect();
+    match shell {
+        "fish" => {
+            let dirs: Vec<String> = dirs.iter().map(|d| single_quote(d, r"\'")).collect();
+            format!(
+                "set -gx PATH {} $PATH;\nset -gx GDK {};\n",
+                dirs.join(" "),
+                single_quote(gdk, r"\'")
+            )
+        }
+        "powershell" => {
+            let sep = if cfg!(target_os = "windows") { ";" } else { ":" };
+            let mut joined = dirs.join(sep);
+            joined.push_str(sep);
+            format!(
+                "$env:PATH = {} + $env:PATH\n$env:GDK = {}\n",
+                single_quote(&joined, "''"),
+                single_quote(gdk, "''")
+            )
+        }
+        // cmd has no quoting inside `set "NAME=value"` beyond the outer quotes
+        "cmd" => format!("set \"PATH={};%PATH%\"\nset \"GDK={gdk}\"\n", dirs.join(";")),
+        _ => format!(
+            "export PATH={}\"${{PATH:+:$PATH}}\"\nexport GDK={}\n",
+            single_quote(&dirs.join(":"), r"'\''"),
+            single_quote(gdk, r"'\''")
+        ),
+    }
+}
+
+/// `s` in single quotes, with embedded single quotes written as `escaped_quote` (each shell
+/// escapes them differently).
+fn single_quote(s: &str, escaped_quote: &str) -> String {
+    format!("'{}'", s.replace('\'', escaped_quote))
+}
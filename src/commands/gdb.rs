@@ -1,4 +1,6 @@
+use crate::error::{Error, OrExit};
 use crate::path;
+use crate::project::{self, Component};
 use clap::Parser;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -13,17 +15,16 @@ pub struct Args {
 /// Thin wrapper: locate the m68k gdb and exec it with the given args verbatim
 /// (e.g. `sgdkx gdb out/rom.out`).
 pub fn run(args: &Args) {
-    let exe = match find_gdb(&path::config_dir()) {
-        Some(p) => p,
-        None => {
-            eprintln!("❌ m68k-elf-gdb not found. Run `sgdkx install` to download it.");
-            std::process::exit(1);
-        }
-    };
+    // gdb ships with the toolchain builds; debug with the one matching the project's gcc
+    project::require_pinned(Path::new("."), Component::Toolchain).or_exit();
+    let exe = find_gdb(&path::config_dir())
+        .ok_or(Error::ToolNotFound("m68k-elf-gdb"))
+        .or_exit();
     let status = Command::new(&exe)
         .args(&args.args)
         .status()
-        .expect("Failed to run m68k-elf-gdb");
+        .map_err(|e| Error::io("failed to run m68k-elf-gdb", e))
+        .or_exit();
     std::process::exit(status.code().unwrap_or(1));
 }
 
@@ -1,80 +1,168 @@
-use crate::release;
+use crate::error::{Error, OrExit, Result};
 use crate::path;
+use crate::project;
+use crate::release;
 use clap::Parser;
 use std::fs;
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use toml_edit::DocumentMut;
 
-#[derive(Parser)]
+#[derive(Parser, Default)]
 pub struct Args {
     /// SGDK version to install: a release tag (e.g. v2.11), "master", or a master-<sha>.
     /// Omitted → interactive pick on a terminal, latest master when non-interactive.
     #[arg(short = 's', long = "sgdk")]
-    sgdk: Option<String>,
+    pub sgdk: Option<String>,
 
     /// BlastEm version: a tag (e.g. build-<sha> for the debug-capable fork, or `nightly` /
     /// nightly-<sha> for upstream). Omitted → interactive pick (debug-capable default),
     /// debug-capable latest when non-interactive.
     #[arg(short = 'b', long = "blastem")]
-    blastem: Option<String>,
+    pub blastem: Option<String>,
+
+    /// Only download the release assets into <DIR>, laid out as a mirror
+    /// (`<repo>/<tag>/<asset>` + `<repo>/releases.json`), without installing. Point
+    /// `SGDKX_MIRROR` at it to install on an offline machine.
+    #[arg(long = "download-only", value_name = "DIR")]
+    pub download_only: Option<std::path::PathBuf>,
+
+    /// Platforms to mirror with --download-only (comma-separated slugs, or `all`).
+    /// Defaults to this machine's platform.
+    #[arg(long = "platform", value_delimiter = ',', requires = "download_only")]
+    pub platforms: Vec<String>,
+
+    /// Accept assets whose release publishes no SHA-256 checksum (a mismatch still aborts)
+    #[arg(long = "allow-unverified")]
+    pub allow_unverified: bool,
+
+    /// Only install/upgrade these components (comma-separated), leaving the others untouched
+    #[arg(
+        long = "only",
+        value_delimiter = ',',
+        value_parser = clap::builder::PossibleValuesParser::new(COMPONENTS),
+        conflicts_with = "download_only"
+    )]
+    pub only: Vec<String>,
 }
 
+/// The components sgdkx installs (names shared with `doctor`, `--only` and
+/// `uninstall --component`). `docs` is the documentation of an SGDK version.
+pub const COMPONENTS: [&str; 6] = ["toolchain", "gdb", "jre", "sgdk", "docs", "blastem"];
+
 pub fn run(args: &Args) {
-    let config_dir = path::config_dir();
-    fs::create_dir_all(&config_dir).expect("Failed to create config directory");
-    install(&config_dir, args);
+    install(args).or_exit();
+}
+
+/// `install`, with every release lookup and download going to `source` instead of the
+/// configured mirror or GitHub (e.g. a `release::Mirror` fixture in tests).
+pub fn install_from(args: &Args, source: std::sync::Arc<dyn release::ReleaseSource>) -> Result<()> {
+    release::with_source(source, || install(args))
 }
 
 // Idempotent install/reconfigure of the self-contained SGDK environment. Re-running is the
-// supported way to *update* (it removes the old SGDK and re-downloads the requested version).
+// supported way to *update*: the requested SGDK is added beside the already-installed versions
+// (see `sgdkx list` / `sgdkx default`) and becomes the default.
 //
 // OS difference: on Unix the gcc toolchain is a separately-cached component (reused across SGDK
 // versions, put on PATH with the `m68k-elf-` prefix); on Windows the toolchain is baked into the
 // self-contained SGDK bundle's `bin/`, so there is no separate toolchain download and no
 // `[toolchain]` entry in config.toml.
-fn install(config_dir: &Path, args: &Args) {
-    let plat = release::platform();
+//
+// Every component is staged and validated before it replaces anything (see "staged installs"),
+// so a failed or interrupted run leaves the previous environment working.
+//
+// Versions left unset in `args` are picked interactively when stdin is a terminal; embedders
+// that must not prompt pass them explicitly.
+pub fn install(args: &Args) -> Result<()> {
+    // clap rejects this on the command line; embedders build `Args` themselves
+    if args.download_only.is_some() && !args.only.is_empty() {
+        return Err(Error::InvalidArgument(
+            "--download-only mirrors every component; it can't be combined with --only".to_string(),
+        ));
+    }
+    let config_dir = &path::try_config_dir()?;
+    fs::create_dir_all(config_dir)
+        .map_err(|e| Error::io(format!("cannot create {}", config_dir.display()), e))?;
+    let plat = release::platform()?;
+    release::set_allow_unverified(args.allow_unverified);
+    let wants = |c: &str| args.only.is_empty() || args.only.iter().any(|o| o == c);
 
     // Resolve versions up front (may prompt) so the rest of the flow is non-interactive.
-    let sgdk_tag = resolve_sgdk_tag(args.sgdk.as_deref());
-    let (blastem_repo, blastem_tag) = resolve_blastem(args.blastem.as_deref());
+    // Omitted versions default to the current project's sgdkx.toml pins, if any. Components
+    // left out by --only aren't resolved (no prompt for them).
+    let pins = project::load_pins(Path::new("."))?;
+    if let Some(file) = project::find_manifest(Path::new(".")) {
+        println!("📌 Using the versions pinned in {}", file.display());
+    }
+    let explicit_sgdk = args.sgdk.as_deref().or(pins.sgdk.as_deref());
+    let sgdk_tag = if wants("sgdk") {
+        Some(resolve_sgdk_tag(explicit_sgdk)?)
+    } else if wants("docs") {
+        // docs alone: for the requested version, else the active one
+        match explicit_sgdk {
+            Some(v) => Some(resolve_sgdk_tag(Some(v))?),
+            None => match path::active_sgdk_version(Path::new(".")) {
+                Some(active) => Some(active),
+                None => Some(resolve_sgdk_tag(None)?),
+            },
+        }
+    } else {
+        None
+    };
+    let blastem = match wants("blastem") {
+        true => Some(resolve_blastem(args.blastem.as_deref().or(pins.blastem.as_deref()))?),
+        false => None,
+    };
+
+    let toolchain_wanted = pins
+        .toolchain
+        .clone()
+        .unwrap_or_else(|| release::TOOLCHAIN_TAG.to_string());
+
+    if let Some(dir) = &args.download_only {
+        let (blastem_repo, blastem_tag) = blastem.expect("--download-only mirrors everything");
+        return populate_mirror(
+            dir,
+            &mirror_platforms(&args.platforms)?,
+            sgdk_tag.as_deref().expect("--download-only mirrors everything"),
+            (blastem_repo, &blastem_tag),
+            &toolchain_wanted,
+        );
+    }
+
+    // Finish or roll back a previous install that was interrupted mid-swap.
+    recover_interrupted(config_dir);
+
+    // Each installer records what it fetched (repo, tag, asset, checksum, date) in config.toml;
+    // a component already present at the wanted tag is kept. One present without a record (an
+    // older sgdkx) is kept too — its tag stays unknown.
 
     // 1. gcc 13 toolchain — Unix only (Windows bundles it inside the SGDK bundle's bin/).
-    #[cfg(not(target_os = "windows"))]
-    {
+    // Cached across SGDK versions; re-fetched only when a project pins a different tag.
+    if wants("toolchain") && cfg!(target_os = "windows") && !args.only.is_empty() {
+        println!("ℹ️  the toolchain is part of the SGDK bundle on Windows");
+    } else if wants("toolchain") && !cfg!(target_os = "windows") {
+        let tag = toolchain_wanted;
         let toolchain_dir = config_dir.join("m68k-elf-toolchain");
-        if toolchain_dir.join("bin").is_dir() {
+        if up_to_date("toolchain", &tag, &toolchain_dir) {
             println!("✅ gcc toolchain already present: {}", toolchain_dir.display());
         } else {
-            println!(
-                "📥 Downloading gcc {} toolchain ({})...",
-                release::TOOLCHAIN_GCC_VERSION, plat
-            );
-            let asset = format!(
-                "m68k-elf-toolchain-gcc{}-{}.tar.gz",
-                release::TOOLCHAIN_GCC_VERSION, plat
-            );
-            let url =
-                release::asset_download_url(release::TOOLCHAIN_REPO, release::TOOLCHAIN_TAG, &asset);
-            if let Err(e) = release::download_tar_gz(&url, config_dir) {
-                eprintln!("❌ failed to fetch toolchain: {e}");
-                std::process::exit(1);
-            }
+            let gcc = release::toolchain_gcc_version(&tag);
+            println!("📥 Downloading gcc {gcc} toolchain ({plat})...");
+            install_toolchain(config_dir, &tag, plat).map_err(|e| Error::install("toolchain", e))?;
             println!("✅ gcc toolchain installed: {}", toolchain_dir.display());
         }
     }
 
     // 1b. m68k-elf-gdb (debugger) — standalone download on every OS. Non-fatal.
-    {
-        let gdb_dir = config_dir.join("m68k-elf-gdb");
-        if gdb_dir.join("bin").is_dir() {
+    let gdb_dir = config_dir.join("m68k-elf-gdb");
+    if wants("gdb") {
+        if up_to_date("gdb", release::GDB_TAG, &gdb_dir) {
             println!("✅ m68k-elf-gdb already present: {}", gdb_dir.display());
         } else {
             println!("📥 Downloading m68k-elf-gdb {} ({})...", release::GDB_VERSION, plat);
-            let asset = format!("m68k-elf-gdb-{}-{}.tar.gz", release::GDB_VERSION, plat);
-            let url = release::asset_download_url(release::GDB_REPO, release::GDB_TAG, &asset);
-            match release::download_tar_gz(&url, config_dir) {
+            match install_gdb(config_dir, plat) {
                 Ok(_) => println!("✅ m68k-elf-gdb installed: {}", gdb_dir.display()),
                 Err(e) => println!("⚠️  m68k-elf-gdb unavailable ({e}); `sgdkx gdb` will not work"),
             }
@@ -83,96 +171,520 @@ fn install(config_dir: &Path, args: &Args) {
 
     // 1c. bundled minimal JRE (for rescomp/sizebnd) — all platforms; download once, reuse
     let jre_dir = config_dir.join("jre");
-    if jre_dir.join("bin").is_dir() {
-        println!("✅ bundled JRE already present: {}", jre_dir.display());
+    if wants("jre") {
+        if up_to_date("jre", release::JRE_TAG, &jre_dir) {
+            println!("✅ bundled JRE already present: {}", jre_dir.display());
+        } else {
+            println!("📥 Downloading bundled JRE ({})...", plat);
+            match install_jre(config_dir, plat) {
+                Ok(_) => println!("✅ JRE installed: {}", jre_dir.display()),
+                Err(e) => println!("⚠️  bundled JRE unavailable ({e}); system Java will be used"),
+            }
+        }
+    }
+
+    // 2. SGDK native bundle (native tools + libmd.a/libmd_debug.a + mac68k) + its prebuilt
+    // documentation, installed side by side under <config>/sdk/<tag>. Release tags are
+    // immutable, so a version already on disk is reused rather than re-downloaded.
+    migrate_legacy_sgdk(config_dir)?;
+    if let Some(tag) = sgdk_tag.as_deref().filter(|_| wants("sgdk")) {
+        let sgdk_dir = path::sgdk_version_dir(tag)?;
+        if sgdk_dir.join("makefile.gen").exists() {
+            println!("✅ SGDK {tag} already installed: {}", sgdk_dir.display());
+        } else {
+            println!("📥 Downloading SGDK {} ({})...", tag, plat);
+            install_sgdk(config_dir, tag, plat).map_err(|e| {
+                Error::install(
+                    &format!("SGDK {tag}"),
+                    format!("{e}\n   (only release tags and 'master' are prebuilt; other commits are built on demand)"),
+                )
+            })?;
+        }
+    }
+
+    // 3. documentation alone (`--only docs`, e.g. after `uninstall --component docs`)
+    if let Some(tag) = sgdk_tag.as_deref().filter(|_| !wants("sgdk") && wants("docs")) {
+        let sgdk_dir = path::sgdk_version_dir(tag)?;
+        let doc_dir = sgdk_dir.join("doc");
+        if !sgdk_dir.join("bin").is_dir() {
+            return Err(Error::SgdkNotInstalled(Some(tag.to_string())));
+        } else if doc_dir.join("html").join("index.html").exists() {
+            println!("✅ SGDK {tag} documentation already present: {}", doc_dir.display());
+        } else {
+            println!("📥 Downloading SGDK {tag} documentation...");
+            match install_docs(config_dir, tag) {
+                Ok(_) => println!("✅ documentation installed: {}", doc_dir.display()),
+                Err(e) => println!("⚠️  documentation not available ({e})"),
+            }
+        }
+    }
+
+    // 4. native BlastEm emulator — standalone download. Non-fatal (only disables `sgdkx blastem`).
+    // The exe is later located by find_blastem; the concrete tag is recorded.
+    if let Some((repo, tag)) = &blastem {
+        download_blastem(config_dir, repo, tag, plat);
+    }
+
+    let Some(sgdk_tag) = sgdk_tag.filter(|_| wants("sgdk")) else {
+        println!("✅ Installed: {}", args.only.join(", "));
+        return Ok(());
+    };
+    // The installed SGDK becomes the default — unless it was only fetched for a project's pin
+    // (the project resolves it anyway) and another default already exists.
+    let default =
+        path::installed_version().filter(|d| path::sgdk_version_dir(d).is_ok_and(|d| d.is_dir()));
+    if args.sgdk.is_some() || pins.sgdk.is_none() || default.is_none() {
+        set_default_sgdk(config_dir, &sgdk_tag)?;
+    } else if let Some(d) = default {
+        println!("ℹ️  default SGDK stays {d} (switch with `sgdkx default {sgdk_tag}`)");
+    }
+    println!("✅ SGDK install complete: {}", path::sgdk_version_dir(&sgdk_tag)?.display());
+    Ok(())
+}
+
+/// Whether the component recorded under `key` is installed at `dir` and not older/other than
+/// `wanted` (a present component without a record — from an older sgdkx — counts as current).
+fn up_to_date(key: &str, wanted: &str, dir: &Path) -> bool {
+    dir.join("bin").is_dir() && path::installed_version_of(key).is_none_or(|v| v == wanted)
+}
+
+// --- staged installs ---
+// Every component is extracted into `<config>/.staging/<name>`, validated, and only then swapped
+// into place by renames. The tree it replaces is parked as `.staging/<name>.old` until the new
+// one is in place, so a failure (network, disk, Ctrl-C) never leaves the component missing; the
+// next run finishes or rolls back an interrupted swap (`recover_interrupted`).
+
+fn staging_root(config_dir: &Path) -> PathBuf {
+    config_dir.join(".staging")
+}
+
+/// Where the component staged as `name` lives once installed (`sdk-<tag>` → `sdk/<tag>`,
+/// `docs-<tag>` → `sdk/<tag>/doc`).
+fn staged_target(config_dir: &Path, name: &str) -> PathBuf {
+    if let Some(tag) = name.strip_prefix("sdk-") {
+        return config_dir.join("sdk").join(tag);
+    }
+    if let Some(tag) = name.strip_prefix("docs-") {
+        return config_dir.join("sdk").join(tag).join("doc");
+    }
+    config_dir.join(name)
+}
+
+/// Download + verify `repo@tag/asset` and extract it into a fresh staging dir for `name`.
+/// Returns the staging dir and the asset's digest.
+fn stage_archive(
+    config_dir: &Path,
+    name: &str,
+    repo: &str,
+    tag: &str,
+    asset: &str,
+) -> Result<(PathBuf, String), String> {
+    let (archive, sha256) = release::download_verified(repo, tag, asset)?;
+    let staged = staging_root(config_dir).join(name);
+    let _ = fs::remove_dir_all(&staged);
+    fs::create_dir_all(&staged).map_err(|e| format!("cannot create {}: {e}", staged.display()))?;
+    let res = if asset.ends_with(".zip") {
+        release::extract_zip(archive.path(), &staged)
     } else {
-        println!("📥 Downloading bundled JRE ({})...", plat);
-        let asset = format!("jre-{}.tar.gz", plat);
-        let url = release::asset_download_url(release::JRE_REPO, release::JRE_TAG, &asset);
-        match release::download_tar_gz(&url, config_dir) {
-            Ok(_) => println!("✅ JRE installed: {}", jre_dir.display()),
-            Err(e) => println!("⚠️  bundled JRE unavailable ({e}); system Java will be used"),
+        release::extract_tar_gz(archive.path(), &staged)
+    };
+    if let Err(e) = res {
+        discard_staged(config_dir, &staged);
+        return Err(e);
+    }
+    Ok((staged, sha256))
+}
+
+/// Drop a staged tree that failed (and the staging root, once empty).
+fn discard_staged(config_dir: &Path, staged: &Path) {
+    let _ = fs::remove_dir_all(staged);
+    let _ = fs::remove_dir(staging_root(config_dir));
+}
+
+/// Swap the validated `tree` (inside `.staging/<name>`, or that dir itself) into the component's
+/// install location, keeping the old tree until the new one is in place.
+fn swap_in(config_dir: &Path, name: &str, tree: &Path) -> Result<(), String> {
+    let target = staged_target(config_dir, name);
+    let staged = staging_root(config_dir).join(name);
+    let old = staging_root(config_dir).join(format!("{name}.old"));
+    let _ = fs::remove_dir_all(&old);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let had_old = fs::symlink_metadata(&target).is_ok();
+    if had_old {
+        fs::rename(&target, &old)
+            .map_err(|e| format!("cannot move aside {}: {e}", target.display()))?;
+    }
+    if let Err(e) = fs::rename(tree, &target) {
+        if had_old {
+            let _ = fs::rename(&old, &target);
         }
+        discard_staged(config_dir, &staged);
+        return Err(format!("cannot move {name} into place: {e}"));
     }
+    let _ = fs::remove_dir_all(&old);
+    let _ = fs::remove_dir_all(&staged);
+    let _ = fs::remove_dir(staging_root(config_dir)); // only if nothing else is staged
+    Ok(())
+}
 
-    // 2. SGDK native bundle (native tools + libmd.a/libmd_debug.a + mac68k)
-    let sgdk_dir = config_dir.join("SGDK");
-    if sgdk_dir.exists() {
-        println!("🗑️  Removing existing SGDK: {}", sgdk_dir.display());
-        fs::remove_dir_all(&sgdk_dir).expect("Failed to remove existing SGDK directory");
+/// Stage `repo@tag/asset`, check that `tree` (relative to the staging dir; "" = the dir itself)
+/// passes `valid`, swap it in, and record it in config.toml under `key`.
+fn install_archive(
+    config_dir: &Path,
+    (name, key): (&str, &str),
+    (repo, tag, asset): (&str, &str, &str),
+    tree: &str,
+    valid: impl Fn(&Path) -> bool,
+) -> Result<(), String> {
+    let (staged, sha256) = stage_archive(config_dir, name, repo, tag, asset)?;
+    let tree = staged.join(tree);
+    if !valid(&tree) {
+        discard_staged(config_dir, &staged);
+        return Err(format!("{asset} has an unexpected layout (extraction problem?)"));
     }
-    println!("📥 Downloading SGDK {} ({})...", sgdk_tag, plat);
-    let asset = format!("sgdk-{}-{}.tar.gz", sgdk_tag, plat);
-    let url = release::asset_download_url(release::SGDK_NATIVE_REPO, &sgdk_tag, &asset);
-    if let Err(e) = release::download_tar_gz(&url, config_dir) {
-        eprintln!("❌ failed to fetch SGDK {sgdk_tag}: {e}");
-        eprintln!("   (only release tags and 'master' are prebuilt; other commits are built on demand)");
-        std::process::exit(1);
+    swap_in(config_dir, name, &tree)?;
+    write_config(config_dir, &[(key, Some(&fetched(repo, tag, asset, sha256)))])
+        .map_err(|e| e.to_string())
+}
+
+/// The config.toml record of an asset installed just now.
+fn fetched(repo: &str, tag: &str, asset: &str, sha256: String) -> path::InstallRecord {
+    path::InstallRecord {
+        version: tag.to_string(),
+        repo: Some(repo.to_string()),
+        asset: Some(asset.to_string()),
+        sha256: Some(sha256),
+        installed: Some(today_utc()),
     }
-    if !sgdk_dir.join("makefile.gen").exists() {
-        eprintln!("❌ SGDK bundle missing makefile.gen — extraction problem?");
-        std::process::exit(1);
+}
+
+/// Today's date in UTC as `YYYY-MM-DD` (days-to-civil conversion; no date crate needed).
+fn today_utc() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Finish or roll back an install that was interrupted: a parked `<name>.old` whose component
+/// is missing is restored, otherwise dropped; half-extracted staging dirs are removed.
+pub fn recover_interrupted(config_dir: &Path) {
+    let root = staging_root(config_dir);
+    let Ok(entries) = fs::read_dir(&root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let p = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        match name.strip_suffix(".old") {
+            Some(component) if fs::symlink_metadata(staged_target(config_dir, component)).is_err() => {
+                let target = staged_target(config_dir, component);
+                match fs::rename(&p, &target) {
+                    Ok(_) => println!("↩️  Restored {} after an interrupted install", target.display()),
+                    Err(e) => eprintln!("⚠️  could not restore {} ({e})", target.display()),
+                }
+            }
+            _ => {
+                let _ = fs::remove_dir_all(&p);
+            }
+        }
+    }
+    let _ = fs::remove_dir(&root);
+}
+
+// Per-component installers: download + verify, stage, validate, swap in, record.
+
+fn install_toolchain(config_dir: &Path, tag: &str, plat: &str) -> Result<(), String> {
+    let asset = release::toolchain_asset(tag, plat);
+    install_archive(
+        config_dir,
+        ("m68k-elf-toolchain", "toolchain"),
+        (release::TOOLCHAIN_REPO, tag, &asset),
+        "m68k-elf-toolchain",
+        |t| t.join("bin").is_dir(),
+    )
+}
+
+fn install_gdb(config_dir: &Path, plat: &str) -> Result<(), String> {
+    let asset = release::gdb_asset(plat);
+    install_archive(
+        config_dir,
+        ("m68k-elf-gdb", "gdb"),
+        (release::GDB_REPO, release::GDB_TAG, &asset),
+        "m68k-elf-gdb",
+        |t| t.join("bin").is_dir(),
+    )
+}
+
+fn install_jre(config_dir: &Path, plat: &str) -> Result<(), String> {
+    let asset = release::jre_asset(plat);
+    install_archive(
+        config_dir,
+        ("jre", "jre"),
+        (release::JRE_REPO, release::JRE_TAG, &asset),
+        "jre",
+        |t| t.join("bin").is_dir(),
+    )
+}
+
+/// SGDK `tag` into `<config>/sdk/<tag>` (recorded as `sdk.<tag>`). The bundle unpacks to a
+/// top-level `SGDK/`, which must contain `makefile.gen`; the docs are added to the staged tree
+/// before the swap (non-fatal), so the version appears complete in one step.
+fn install_sgdk(config_dir: &Path, tag: &str, plat: &str) -> Result<(), String> {
+    let name = format!("sdk-{tag}");
+    let repo = release::SGDK_NATIVE_REPO;
+    let asset = sgdk_asset(tag, plat);
+    let (staged, sha256) = stage_archive(config_dir, &name, repo, tag, &asset)?;
+    let tree = staged.join("SGDK");
+    if !tree.join("makefile.gen").exists() {
+        discard_staged(config_dir, &staged);
+        return Err("SGDK bundle missing makefile.gen — extraction problem?".into());
     }
 
-    // 3. prebuilt documentation (server-side doxygen), extracted into SGDK/doc/html
+    // prebuilt documentation (server-side doxygen), extracted into <sgdk>/doc/html
     println!("📥 Downloading SGDK documentation...");
-    let docs_asset = format!("sgdk-docs-{}.tar.gz", sgdk_tag);
-    let docs_url = release::asset_download_url(release::SGDK_NATIVE_REPO, &sgdk_tag, &docs_asset);
-    match release::download_tar_gz(&docs_url, &sgdk_dir.join("doc")) {
-        Ok(_) => println!("✅ documentation installed: {}", sgdk_dir.join("doc/html").display()),
-        Err(e) => println!("⚠️  documentation not available ({e})"),
+    let docs = docs_asset(tag);
+    let docs_record = match release::download_tar_gz(repo, tag, &docs, &tree.join("doc")) {
+        Ok(docs_sha256) => {
+            println!("✅ documentation downloaded");
+            Some(fetched(repo, tag, &docs, docs_sha256))
+        }
+        Err(e) => {
+            println!("⚠️  documentation not available ({e})");
+            None
+        }
+    };
+    swap_in(config_dir, &name, &tree)?;
+    write_config(
+        config_dir,
+        &[
+            (&format!("sdk.{tag}"), Some(&fetched(repo, tag, &asset, sha256))),
+            (&format!("docs.{tag}"), docs_record.as_ref()),
+        ],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// SGDK `tag`'s documentation alone into `<config>/sdk/<tag>/doc` (the archive unpacks to
+/// `html/`).
+fn install_docs(config_dir: &Path, tag: &str) -> Result<(), String> {
+    install_archive(
+        config_dir,
+        (&format!("docs-{tag}"), &format!("docs.{tag}")),
+        (release::SGDK_NATIVE_REPO, tag, &docs_asset(tag)),
+        "",
+        |t| t.join("html").join("index.html").exists(),
+    )
+}
+
+/// Re-fetch one component — a `doctor` check name: sgdk, docs, toolchain, jre, gdb, blastem —
+/// at the version the environment expects (project pin, else what config.toml records, else
+/// the default), leaving everything else alone. Used by `sgdkx doctor --fix`; the versions
+/// it installs are recorded in config.toml.
+pub fn repair(config_dir: &Path, component: &str) -> Result<()> {
+    recover_interrupted(config_dir);
+    let plat = release::platform()?;
+    let pins = project::load_pins(Path::new("."))?;
+    let failed = |e| Error::install(component, e);
+    match component {
+        "sgdk" | "docs" => {
+            migrate_legacy_sgdk(config_dir)?;
+            let tag = path::active_sgdk_version(Path::new(".")).ok_or(Error::SgdkNotInstalled(None))?;
+            if component == "docs" {
+                println!("📥 Downloading SGDK {tag} documentation...");
+                return install_docs(config_dir, &tag).map_err(failed);
+            }
+            println!("📥 Downloading SGDK {tag} ({plat})...");
+            install_sgdk(config_dir, &tag, plat).map_err(failed)?;
+            if path::installed_version().is_none() {
+                set_default_sgdk(config_dir, &tag)?;
+            }
+        }
+        "toolchain" => {
+            let tag = pins
+                .toolchain
+                .or_else(|| path::installed_version_of("toolchain"))
+                .unwrap_or_else(|| release::TOOLCHAIN_TAG.to_string());
+            let gcc = release::toolchain_gcc_version(&tag);
+            println!("📥 Downloading gcc {gcc} toolchain ({plat})...");
+            install_toolchain(config_dir, &tag, plat).map_err(failed)?;
+        }
+        "gdb" => {
+            println!("📥 Downloading m68k-elf-gdb {} ({plat})...", release::GDB_VERSION);
+            install_gdb(config_dir, plat).map_err(failed)?;
+        }
+        "jre" => {
+            println!("📥 Downloading bundled JRE ({plat})...");
+            install_jre(config_dir, plat).map_err(failed)?;
+        }
+        "blastem" => {
+            let wanted = pins
+                .blastem
+                .or_else(|| path::installed_version_of("blastem"))
+                .unwrap_or_else(|| "latest".to_string());
+            let (repo, tag) = resolve_blastem(Some(&wanted))?;
+            download_blastem(config_dir, repo, &tag, plat).ok_or_else(|| failed("download failed".into()))?;
+        }
+        other => return Err(Error::InvalidArgument(format!("{other} isn't installed by sgdkx"))),
     }
+    Ok(())
+}
 
-    // 4. native BlastEm emulator — standalone download. Non-fatal (only disables `sgdkx blastem`).
-    // Install is a side effect; the exe is later located by find_blastem, not stored in config.
-    download_blastem(config_dir, blastem_repo, &blastem_tag);
+/// Install `component` (`sgdk`, `toolchain`, `gdb`, `jre`, `blastem`) at release `tag` from
+/// `repo` — `sgdkx update` bringing a stale component up to date. A new SGDK version is added
+/// beside the others and becomes the default; its docs come with it.
+pub fn upgrade(config_dir: &Path, component: &str, repo: &str, tag: &str) -> Result<()> {
+    recover_interrupted(config_dir);
+    let plat = release::platform()?;
+    let failed = |e| Error::install(component, e);
+    match component {
+        "sgdk" => {
+            migrate_legacy_sgdk(config_dir)?;
+            println!("📥 Downloading SGDK {tag} ({plat})...");
+            install_sgdk(config_dir, tag, plat).map_err(failed)?;
+            set_default_sgdk(config_dir, tag)?;
+        }
+        "toolchain" => {
+            let gcc = release::toolchain_gcc_version(tag);
+            println!("📥 Downloading gcc {gcc} toolchain ({plat})...");
+            install_toolchain(config_dir, tag, plat).map_err(failed)?;
+        }
+        // fixed-tag components: sgdkx pins the one release it installs
+        "gdb" => {
+            println!("📥 Downloading m68k-elf-gdb {} ({plat})...", release::GDB_VERSION);
+            install_gdb(config_dir, plat).map_err(failed)?;
+        }
+        "jre" => {
+            println!("📥 Downloading bundled JRE ({plat})...");
+            install_jre(config_dir, plat).map_err(failed)?;
+        }
+        "blastem" => {
+            download_blastem(config_dir, repo, tag, plat).ok_or_else(|| failed("download failed".into()))?;
+        }
+        other => return Err(Error::InvalidArgument(format!("{other} isn't installed by sgdkx"))),
+    }
+    Ok(())
+}
 
-    // config.toml records only the installed SGDK version. Every path is derived from the fixed
-    // install layout under config_dir (see path.rs), so nothing else needs to be stored.
-    write_config(config_dir, &sgdk_tag);
-    println!("✅ SGDK install complete: {}", sgdk_dir.display());
+/// Move a pre-side-by-side install (a real `<config>/SGDK` directory) to `<config>/sdk/<tag>`,
+/// named after the recorded version, so it stays usable and `<config>/SGDK` can become the
+/// default-version link.
+fn migrate_legacy_sgdk(config_dir: &Path) -> Result<()> {
+    let legacy = config_dir.join("SGDK");
+    let is_real_dir = fs::symlink_metadata(&legacy).is_ok_and(|m| m.is_dir());
+    if !is_real_dir {
+        return Ok(());
+    }
+    let tag = path::installed_version().unwrap_or_else(|| "legacy".to_string());
+    let dest = path::sgdk_version_dir(&tag)?;
+    fs::create_dir_all(path::sdk_root()?)
+        .map_err(|e| Error::io("cannot create the sdk directory", e))?;
+    if dest.exists() {
+        fs::remove_dir_all(&legacy)
+            .map_err(|e| Error::io("cannot remove the legacy SGDK directory", e))?;
+    } else {
+        println!("📦 Moving existing SGDK {tag} to {}", dest.display());
+        fs::rename(&legacy, &dest)
+            .map_err(|e| Error::io("cannot migrate the legacy SGDK directory", e))?;
+    }
+    if path::installed_version().is_some() {
+        relink_default_sgdk(config_dir, &tag);
+    }
+    Ok(())
+}
+
+/// Make `tag` (already installed under `<config>/sdk`) the default SGDK: record it in
+/// config.toml and point the `<config>/SGDK` link at it.
+pub fn set_default_sgdk(config_dir: &Path, tag: &str) -> Result<()> {
+    migrate_legacy_sgdk(config_dir)?;
+    let default = path::InstallRecord {
+        version: tag.to_string(),
+        ..Default::default()
+    };
+    write_config(config_dir, &[("sgdk", Some(&default))])?;
+    relink_default_sgdk(config_dir, tag);
+    println!("📌 Default SGDK: {tag}");
+    Ok(())
+}
+
+/// (Re)point `<config>/SGDK` at `sdk/<tag>`: a relative symlink on Unix, a directory junction on
+/// Windows (no admin rights needed). Non-fatal — only home-relative editor/Makefile paths rely
+/// on it; `sgdkx make` resolves the version itself.
+fn relink_default_sgdk(config_dir: &Path, tag: &str) {
+    let link = config_dir.join("SGDK");
+    // Unix: build the new link beside the old one and rename over it (never a moment without)
+    #[cfg(unix)]
+    let res = {
+        let tmp = config_dir.join(".SGDK.tmp");
+        let _ = fs::remove_file(&tmp);
+        std::os::unix::fs::symlink(Path::new("sdk").join(tag), &tmp)
+            .and_then(|_| fs::rename(&tmp, &link))
+    };
+    #[cfg(windows)]
+    if fs::symlink_metadata(&link).is_ok() {
+        let _ = fs::remove_dir(&link).or_else(|_| fs::remove_file(&link));
+    }
+    #[cfg(windows)]
+    let res = std::process::Command::new("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(&link)
+        .arg(config_dir.join("sdk").join(tag))
+        .output()
+        .and_then(|o| {
+            o.status
+                .success()
+                .then_some(())
+                .ok_or_else(|| std::io::Error::other(String::from_utf8_lossy(&o.stderr).into_owned()))
+        });
+    if let Err(e) = res {
+        println!("⚠️  could not link {} to SGDK {tag} ({e})", link.display());
+    }
 }
 
 /// Resolve the SGDK release tag to install.
 /// Explicit flag wins ("master" → newest master-<sha>); otherwise interactive on a terminal,
 /// latest master when non-interactive (scriptable default).
-fn resolve_sgdk_tag(explicit: Option<&str>) -> String {
+fn resolve_sgdk_tag(explicit: Option<&str>) -> Result<String> {
     match explicit {
-        Some("master") => latest_master_or_exit(),
-        Some(v) => v.to_string(),
-        None => pick_or(release::SGDK_NATIVE_REPO, "Select an SGDK version", latest_master_or_exit),
+        Some("master") => latest_master(),
+        Some(v) => Ok(v.to_string()),
+        None => pick_or(release::SGDK_NATIVE_REPO, "Select an SGDK version", latest_master),
     }
 }
 
-/// Resolve `(repo, tag)` for the BlastEm download. An explicit `--blastem` wins: a `nightly`
-/// / `nightly-<sha>` value routes to the upstream nightly repo, anything else (e.g. `latest`,
-/// `build-<sha>`) to the debug-capable fork. Without it: a two-stage interactive pick on a
-/// terminal, else the debug-capable latest (scriptable default).
-fn resolve_blastem(explicit: Option<&str>) -> (&'static str, String) {
+/// Resolve `(repo, tag)` for the BlastEm download. An explicit `--blastem` wins, routed by
+/// `release::blastem_source`. Without it: a two-stage interactive pick on a terminal, else the
+/// debug-capable latest (scriptable default).
+fn resolve_blastem(explicit: Option<&str>) -> Result<(&'static str, String)> {
     if let Some(v) = explicit {
-        return if v == "nightly" {
-            (release::BLASTEM_NIGHTLY_REPO, "latest".to_string())
-        } else if v.starts_with("nightly-") {
-            (release::BLASTEM_NIGHTLY_REPO, v.to_string())
-        } else {
-            (release::BLASTEM_DEBUG_REPO, v.to_string())
-        };
+        return Ok(release::blastem_source(v));
     }
     if std::io::stdin().is_terminal() {
         pick_blastem()
     } else {
-        (release::BLASTEM_DEBUG_REPO, "latest".to_string())
+        Ok((release::BLASTEM_DEBUG_REPO, "latest".to_string()))
     }
 }
 
 /// Two-stage pick: first the source (debug-capable first/default, or the original upstream
 /// nightly), then the version. A lone debug-capable build is taken immediately; otherwise the
 /// versions are listed with a date hint. Falls back to that source's "latest" if listing fails.
-fn pick_blastem() -> (&'static str, String) {
+fn pick_blastem() -> Result<(&'static str, String)> {
     let source = pick(
         "Select a BlastEm source",
         &["debug-capable".to_string(), "nightly (original)".to_string()],
-    );
+    )?;
     let repo = if source == "debug-capable" {
         release::BLASTEM_DEBUG_REPO
     } else {
@@ -181,32 +693,32 @@ fn pick_blastem() -> (&'static str, String) {
     match release::list_releases_with_dates(repo) {
         Ok(rels) => {
             if repo == release::BLASTEM_DEBUG_REPO && rels.len() == 1 {
-                (repo, rels[0].0.clone()) // single debug-capable build: no version prompt
+                Ok((repo, rels[0].0.clone())) // single debug-capable build: no version prompt
             } else {
-                (repo, pick_release("Select a BlastEm version", &rels))
+                Ok((repo, pick_release("Select a BlastEm version", &rels)?))
             }
         }
         Err(e) => {
             eprintln!("⚠️  could not list versions ({e}); using latest");
-            (repo, "latest".to_string())
+            Ok((repo, "latest".to_string()))
         }
     }
 }
 
 /// Like `pick`, but renders each release as `<tag>   (<date>)` and returns the chosen tag.
-fn pick_release(prompt: &str, rels: &[(String, String)]) -> String {
+fn pick_release(prompt: &str, rels: &[(String, String)]) -> Result<String> {
     let labels: Vec<String> = rels
         .iter()
         .map(|(t, d)| if d.is_empty() { t.clone() } else { format!("{t}   ({d})") })
         .collect();
-    let chosen = pick(prompt, &labels);
+    let chosen = pick(prompt, &labels)?;
     let idx = labels.iter().position(|l| *l == chosen).unwrap_or(0);
-    rels[idx].0.clone()
+    Ok(rels[idx].0.clone())
 }
 
 /// On a terminal, list `repo`'s release tags and let the user pick one; otherwise — or if the
 /// list can't be fetched — fall back to `latest()` (the scriptable, non-interactive default).
-fn pick_or(repo: &str, prompt: &str, latest: impl Fn() -> String) -> String {
+fn pick_or(repo: &str, prompt: &str, latest: impl Fn() -> Result<String>) -> Result<String> {
     if std::io::stdin().is_terminal() {
         match release::list_release_tags(repo) {
             Ok(tags) => return pick(prompt, &tags),
@@ -216,110 +728,286 @@ fn pick_or(repo: &str, prompt: &str, latest: impl Fn() -> String) -> String {
     latest()
 }
 
-fn latest_master_or_exit() -> String {
-    match release::latest_master_tag(release::SGDK_NATIVE_REPO) {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("❌ failed to resolve latest master release: {e}");
-            std::process::exit(1);
-        }
-    }
+fn latest_master() -> Result<String> {
+    release::latest_master_tag(release::SGDK_NATIVE_REPO)
+        .map_err(|e| Error::Release(format!("failed to resolve latest master release: {e}")))
 }
 
 /// Interactive single-select over `items` (default = first/newest). Esc cancels the install.
-fn pick(prompt: &str, items: &[String]) -> String {
+fn pick(prompt: &str, items: &[String]) -> Result<String> {
     use dialoguer::{Select, theme::ColorfulTheme};
     let selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt(format!("{prompt} (Esc to cancel)"))
         .items(items)
         .default(0)
         .interact_opt()
-        .unwrap();
-    match selection {
-        Some(idx) => items[idx].clone(),
-        None => {
-            println!("Cancelled.");
-            std::process::exit(0);
-        }
+        .map_err(|e| Error::io("version prompt failed", std::io::Error::other(e)))?;
+    let idx = selection.ok_or(Error::Cancelled)?;
+    Ok(items[idx].clone())
+}
+
+// Release asset names per component + platform slug (shared by install and --download-only; the
+// fixed-tag components' are in `release`, next to their pinned digests).
+fn sgdk_asset(tag: &str, plat: &str) -> String {
+    format!("sgdk-{tag}-{plat}.tar.gz")
+}
+
+fn docs_asset(sgdk_tag: &str) -> String {
+    format!("sgdk-docs-{sgdk_tag}.tar.gz")
+}
+
+/// BlastEm asset name prefix + archive kind (`true` = zip) per platform; the asset names are
+/// version-suffixed, so they are looked up by prefix.
+fn blastem_asset(plat: &str) -> Option<(&'static str, bool)> {
+    match plat {
+        "macos-arm64" => Some(("BlastEm-macOS-arm64-", true)),
+        "macos-x86_64" => Some(("BlastEm-macOS-x86_64-", true)),
+        "linux-x86_64" => Some(("blastem-linux-x86_64-", false)),
+        "linux-arm64" => Some(("blastem-linux-arm64-", false)),
+        "windows-x86_64" => Some(("blastem-win64-", true)),
+        _ => None,
     }
 }
 
-/// Download a native BlastEm build into `<config>/blastem` and return its executable path.
-/// Returns None (after a warning) on any failure — BlastEm is optional.
-fn download_blastem(config_dir: &Path, repo: &str, tag: &str) -> Option<std::path::PathBuf> {
-    let plat = release::platform();
-    let install_dir = config_dir.join("blastem");
-    if install_dir.exists() {
-        let _ = fs::remove_dir_all(&install_dir);
+/// Platform slugs for --download-only: the requested ones (`all` = every platform), else this
+/// machine's.
+fn mirror_platforms(requested: &[String]) -> Result<Vec<&'static str>> {
+    if requested.is_empty() {
+        return Ok(vec![release::platform()?]);
     }
-    if let Err(e) = fs::create_dir_all(&install_dir) {
-        eprintln!("⚠️  could not create blastem dir ({e}); `sgdkx blastem` will not work");
-        return None;
+    if requested.iter().any(|p| p == "all") {
+        return Ok(release::ALL_PLATFORMS.to_vec());
     }
+    requested
+        .iter()
+        .map(|p| {
+            let known = release::ALL_PLATFORMS.iter().find(|k| **k == p.as_str());
+            known.copied().ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "unknown platform '{p}' (expected one of: {}, all)",
+                    release::ALL_PLATFORMS.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
 
-    // asset prefix + archive kind per platform (asset names are version-suffixed)
-    let (prefix, is_zip) = match plat {
-        "macos-arm64" => ("BlastEm-macOS-arm64-", true),
-        "macos-x86_64" => ("BlastEm-macOS-x86_64-", true),
-        "linux-x86_64" => ("blastem-linux-x86_64-", false),
-        "linux-arm64" => ("blastem-linux-arm64-", false),
-        "windows-x86_64" => ("blastem-win64-", true),
-        other => {
-            eprintln!("⚠️  no BlastEm build for platform {other}; `sgdkx blastem` will not work");
-            return None;
+/// `--download-only`: fetch the resolved versions' release assets for `platforms` into a mirror
+/// directory (`<repo>/<tag>/<asset>`) and record them in each repo's `releases.json` index, so
+/// `SGDKX_MIRROR=<dir> sgdkx install ...` works on an air-gapped machine. Already-mirrored
+/// assets are kept. SGDK and the toolchain are required; the other components only warn.
+fn populate_mirror(
+    dir: &Path,
+    platforms: &[&str],
+    sgdk_tag: &str,
+    (blastem_repo, blastem_tag): (&'static str, &str),
+    toolchain_tag: &str,
+) -> Result<()> {
+    let blastem_tag = match release::resolve_tag(blastem_repo, blastem_tag) {
+        Ok(t) => Some(t),
+        Err(e) => {
+            eprintln!("⚠️  BlastEm not mirrored ({e})");
+            None
         }
     };
 
-    println!("📥 Downloading native BlastEm {tag} from {repo} ({plat})...");
-    let url = match release::find_asset_url(repo, tag, prefix) {
-        Ok(u) => u,
+    // (repo, tag, asset, required)
+    let mut wanted: Vec<(&str, String, String, bool)> = Vec::new();
+    for plat in platforms {
+        if !plat.starts_with("windows") {
+            let asset = release::toolchain_asset(toolchain_tag, plat);
+            wanted.push((release::TOOLCHAIN_REPO, toolchain_tag.to_string(), asset, true));
+        }
+        let asset = release::gdb_asset(plat);
+        wanted.push((release::GDB_REPO, release::GDB_TAG.to_string(), asset, false));
+        let asset = release::jre_asset(plat);
+        wanted.push((release::JRE_REPO, release::JRE_TAG.to_string(), asset, false));
+        let asset = sgdk_asset(sgdk_tag, plat);
+        wanted.push((release::SGDK_NATIVE_REPO, sgdk_tag.to_string(), asset, true));
+        if let (Some(tag), Some((prefix, _))) = (&blastem_tag, blastem_asset(plat)) {
+            match release::find_asset_name(blastem_repo, tag, prefix) {
+                Ok(asset) => wanted.push((blastem_repo, tag.clone(), asset, false)),
+                Err(e) => eprintln!("⚠️  BlastEm for {plat} not mirrored ({e})"),
+            }
+        }
+    }
+    let docs = docs_asset(sgdk_tag);
+    wanted.push((release::SGDK_NATIVE_REPO, sgdk_tag.to_string(), docs, false));
+
+    // assets mirrored per (repo, tag), for the index
+    let mut mirrored: Vec<(&str, String, Vec<String>)> = Vec::new();
+    for (repo, tag, asset, required) in wanted {
+        let dest_dir = dir.join(repo).join(&tag);
+        fs::create_dir_all(&dest_dir)
+            .map_err(|e| Error::io(format!("cannot create {}", dest_dir.display()), e))?;
+        let dest = dest_dir.join(&asset);
+        if dest.exists() {
+            println!("✅ already mirrored: {repo}/{tag}/{asset}");
+        } else {
+            println!("📥 Downloading {repo}/{tag}/{asset}...");
+            // verified into a temp file first: a failed run never leaves a bad asset behind
+            if let Err(e) = release::download_verified(repo, &tag, &asset).and_then(|tmp| {
+                fs::copy(tmp.0.path(), &dest).map(|_| ()).map_err(|e| e.to_string())
+            }) {
+                let _ = fs::remove_file(&dest);
+                if required {
+                    return Err(Error::Release(format!("failed to fetch {asset}: {e}")));
+                }
+                eprintln!("⚠️  {asset} not mirrored ({e})");
+                continue;
+            }
+        }
+        match mirrored.iter_mut().find(|(r, t, _)| *r == repo && *t == tag) {
+            Some((_, _, assets)) => assets.push(asset),
+            None => mirrored.push((repo, tag, vec![asset])),
+        }
+    }
+
+    // carry each release's checksum list along, so offline installs verify too
+    for (repo, tag, assets) in &mut mirrored {
+        let dest = dir.join(*repo).join(&*tag).join(release::SHA256SUMS);
+        if let Ok(Some(sums)) = release::fetch_sha256sums(repo, tag)
+            && fs::write(&dest, sums).is_ok()
+        {
+            assets.push(release::SHA256SUMS.to_string());
+        }
+    }
+
+    for (repo, tag, assets) in &mirrored {
+        // keep upstream publish date + body (the picker's date hint); a bare tag if unavailable
+        let rel = release::release_json(repo, tag)
+            .unwrap_or_else(|_| serde_json::json!({ "tag_name": tag }));
+        release::mirror_index_add(dir, repo, &rel, assets).map_err(|e| {
+            Error::io("failed to update the mirror index", std::io::Error::other(e))
+        })?;
+    }
+    println!("✅ Mirror ready: {}", dir.display());
+    println!("   Offline: SGDKX_MIRROR={} sgdkx install --sgdk {sgdk_tag}", dir.display());
+    Ok(())
+}
+
+/// Download a native BlastEm build into `<config>/blastem` and return the concrete release tag
+/// installed ("latest" resolved). A build config.toml records as already installed (same repo
+/// and tag, executable present) is kept. Returns None (after a warning) on any failure —
+/// BlastEm is optional.
+fn download_blastem(config_dir: &Path, repo: &str, tag: &str, plat: &str) -> Option<String> {
+    let Some((prefix, _)) = blastem_asset(plat) else {
+        eprintln!("⚠️  no BlastEm build for platform {plat}; `sgdkx blastem` will not work");
+        return None;
+    };
+
+    let tag = match release::resolve_tag(repo, tag) {
+        Ok(t) => t,
         Err(e) => {
             eprintln!("⚠️  BlastEm unavailable ({e}); `sgdkx blastem` will not work");
             return None;
         }
     };
-    let res = if is_zip {
-        release::download_zip(&url, &install_dir)
-    } else {
-        release::download_tar_gz(&url, &install_dir)
-    };
+    let current = path::install_record("blastem");
+    let same_build = current
+        .is_some_and(|r| r.version == tag && r.repo.as_deref().is_none_or(|r| r == repo));
+    if let Some(exe) = crate::commands::blastem::find_blastem(config_dir).filter(|_| same_build) {
+        println!("✅ BlastEm {tag} already installed: {}", exe.display());
+        return Some(tag);
+    }
+    println!("📥 Downloading native BlastEm {tag} from {repo} ({plat})...");
+    // the archive's layout differs per platform: validate by locating the executable
+    let res = release::find_asset_name(repo, &tag, prefix).and_then(|asset| {
+        install_archive(config_dir, ("blastem", "blastem"), (repo, &tag, &asset), "", |t| {
+            crate::commands::blastem::find_blastem(t.parent().unwrap_or(t)).is_some()
+        })
+    });
     if let Err(e) = res {
         eprintln!("⚠️  failed to install BlastEm ({e}); `sgdkx blastem` will not work");
         return None;
     }
-    match crate::commands::blastem::find_blastem(config_dir) {
-        Some(exe) => {
-            println!("✅ BlastEm installed: {}", exe.display());
-            Some(exe)
-        }
-        None => {
-            eprintln!("⚠️  BlastEm binary not found after extraction; `sgdkx blastem` will not work");
-            None
-        }
-    }
+    let exe = crate::commands::blastem::find_blastem(config_dir)?;
+    println!("✅ BlastEm installed: {}", exe.display());
+    Some(tag)
 }
 
-/// Write config.toml: the single non-derivable fact — which SGDK native-build version is
-/// installed. Every path is derived from the fixed install layout under config_dir (see
-/// path.rs), so nothing else is stored; legacy path/toolchain/jre/emulator entries written by
-/// older sgdkx versions are dropped on rewrite.
-fn write_config(config_dir: &Path, version: &str) {
-    use toml_edit::{InlineTable, Item, Value};
+/// Write config.toml: the non-derivable facts — what is installed, at which release tag, and
+/// where from. Each component is a table (`version` = tag, `repo`, `asset`, `sha256`,
+/// `installed` date); SGDK versions and their docs sit under `[sdk.<tag>]` / `[docs.<tag>]`
+/// (keys `sdk.<tag>` / `docs.<tag>` here), and `sgdk = { version = "<tag>" }` names the default.
+/// A record with only a version updates just the version (keeping the source fields if it is
+/// unchanged); `None` drops the entry (e.g. a component just uninstalled). Every path is derived
+/// from the fixed install layout under config_dir (see path.rs); legacy path-only / emulator
+/// entries written by older sgdkx versions are dropped on rewrite.
+pub fn write_config(
+    config_dir: &Path,
+    records: &[(&str, Option<&path::InstallRecord>)],
+) -> Result<()> {
+    use toml_edit::{InlineTable, Item, Table, Value, value};
     let config_path = config_dir.join("config.toml");
     let mut doc = if config_path.exists() {
-        fs::read_to_string(&config_path)
-            .expect("config.toml read failed")
-            .parse::<DocumentMut>()
-            .expect("TOML parse failed")
+        let text = fs::read_to_string(&config_path)
+            .map_err(|e| Error::io(format!("cannot read {}", config_path.display()), e))?;
+        text.parse::<DocumentMut>().map_err(|e| {
+            Error::io(
+                format!("cannot parse {}", config_path.display()),
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+            )
+        })?
     } else {
         DocumentMut::new()
     };
 
-    let mut sgdk = InlineTable::new();
-    sgdk.insert("version", Value::from(version));
-    doc.insert("sgdk", Item::Value(Value::InlineTable(sgdk)));
-    for legacy in ["toolchain", "jre", "emulator"] {
-        doc.remove(legacy);
+    for (key, record) in records {
+        // `sdk.<tag>` → the `<tag>` entry of the implicit `[sdk]` table
+        let (parent, name) = match key.split_once('.') {
+            Some((table, tag)) => {
+                let t = doc.entry(table).or_insert(Item::Table(Table::new()));
+                let Some(t) = t.as_table_mut() else {
+                    continue;
+                };
+                t.set_implicit(true);
+                (t, tag)
+            }
+            None => (doc.as_table_mut(), *key),
+        };
+        let Some(record) = record else {
+            parent.remove(name);
+            continue;
+        };
+        if record.repo.is_none() {
+            let unchanged = parent
+                .get(name)
+                .and_then(|e| e.get("version"))
+                .and_then(|v| v.as_str())
+                == Some(record.version.as_str());
+            if !unchanged {
+                let mut entry = InlineTable::new();
+                entry.insert("version", Value::from(record.version.as_str()));
+                parent.insert(name, Item::Value(Value::InlineTable(entry)));
+            }
+            continue;
+        }
+        let mut entry = Table::new();
+        entry.insert("version", value(record.version.as_str()));
+        let fields = [
+            ("repo", &record.repo),
+            ("asset", &record.asset),
+            ("sha256", &record.sha256),
+            ("installed", &record.installed),
+        ];
+        for (field, v) in fields {
+            if let Some(v) = v {
+                entry.insert(field, value(v.as_str()));
+            }
+        }
+        parent.insert(name, Item::Table(entry));
+    }
+    // entries from before version recording held paths only (and `emulator` is gone)
+    doc.remove("emulator");
+    for legacy in ["jre", "gdb"] {
+        if doc.get(legacy).and_then(|e| e.get("version")).is_none() {
+            doc.remove(legacy);
+        }
     }
-    fs::write(&config_path, doc.to_string()).expect("Failed to write config.toml");
+    // write-then-rename: an interrupted write never leaves a truncated config.toml
+    let tmp = config_dir.join("config.toml.tmp");
+    fs::write(&tmp, doc.to_string())
+        .and_then(|_| fs::rename(&tmp, &config_path))
+        .map_err(|e| Error::io(format!("cannot write {}", config_path.display()), e))
 }
@@ -0,0 +1,39 @@
+use crate::error::OrExit;
+use crate::path;
+use clap::Parser;
+use std::path::Path;
+
+#[derive(Parser)]
+pub struct Args {}
+
+/// List the SGDK versions installed side by side, marking the default and the current
+/// project's pin.
+pub fn run(_args: &Args) {
+    let versions = path::installed_sgdk_versions();
+    if versions.is_empty() {
+        println!("No SGDK versions installed. Please run `sgdkx install`.");
+        return;
+    }
+    let default = path::installed_version();
+    let pinned = crate::project::pins_for(Path::new(".")).sgdk;
+
+    println!("SGDK versions ({}):", path::sdk_root().or_exit().display());
+    for tag in &versions {
+        let mut notes = Vec::new();
+        if default.as_ref() == Some(tag) {
+            notes.push("default");
+        }
+        if pinned.as_ref() == Some(tag) {
+            notes.push("pinned by sgdkx.toml");
+        }
+        let marker = if default.as_ref() == Some(tag) { "*" } else { " " };
+        if notes.is_empty() {
+            println!("{marker} {tag}");
+        } else {
+            println!("{marker} {tag}   ({})", notes.join(", "));
+        }
+    }
+    if let Some(p) = pinned.filter(|p| !versions.contains(p)) {
+        println!("⚠️  this project pins SGDK {p}, which is not installed (`sgdkx install --sgdk {p}`)");
+    }
+}
@@ -1,7 +1,9 @@
+use crate::error::{Error, OrExit, Result};
 use crate::path;
+use crate::project::{self, Component};
 use clap::Parser;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
 
 #[derive(Parser)]
 pub struct Args {
@@ -11,28 +13,40 @@ pub struct Args {
 }
 
 /// Thin wrapper around `make`: prepend the build tool dirs to PATH, then run `make`
-/// (bare) with the given args verbatim. You can also run `make` directly if you put
-/// those directories on PATH yourself.
+/// (bare) with the given args verbatim. You can also run `make` directly with those
+/// directories on PATH and `GDK` set — `sgdkx env` prints them for your shell.
 pub fn run(args: &Args) {
     let argv: Vec<&str> = args.args.iter().map(String::as_str).collect();
-    let status = make_command(&argv)
-        .status()
-        .unwrap_or_else(|e| {
-            eprintln!("❌ failed to run make: {e}");
-            std::process::exit(1);
-        });
+    let status = build(Path::new("."), &argv).or_exit();
     std::process::exit(status.code().unwrap_or(1));
 }
 
-/// Build a Command that runs `make <make_args>` with PATH prepared.
+/// Run `make <make_args>` in `project_dir` against its pinned (or the default) SGDK, output
+/// going to this process's stdout/stderr. Returns make's exit status.
+pub fn build(project_dir: &Path, make_args: &[&str]) -> Result<ExitStatus> {
+    require_build_pins(project_dir)?;
+    make_command(project_dir, make_args)?
+        .status()
+        .map_err(|e| Error::io("failed to run make", e))
+}
+
+/// Refuse to build a project whose sgdkx.toml pins an SGDK / toolchain other than the installed
+/// one.
+pub fn require_build_pins(project_dir: &Path) -> Result<()> {
+    project::require_pinned(project_dir, Component::Sgdk)?;
+    project::require_pinned(project_dir, Component::Toolchain)
+}
+
+/// Build a Command that runs `make <make_args>` in `project_dir` with PATH prepared for the
+/// SGDK version that project resolves to.
 ///
 /// On Windows we run make *inside* SGDK's bundled MSYS `sh` (`sh -c "make ..."`).
 /// Launched directly via CreateProcess, MSYS make's self-restart (after generating the
 /// `.d` includes) and gcc `-flto`'s parallel make fail with quoted argv ('"make":
 /// Command not found'). Running under MSYS sh gives the native environment SGDK expects
 /// on Windows, where the restart/recursion work. On Unix we exec `make` directly.
-pub fn make_command(make_args: &[&str]) -> Command {
-    prepend_tool_path();
+pub fn make_command(project_dir: &Path, make_args: &[&str]) -> Result<Command> {
+    prepend_tool_path(project_dir)?;
     #[cfg(target_os = "windows")]
     {
         // Build `make <args>` for the MSYS sh. Single-quote each arg (escaping embedded single
@@ -44,34 +58,35 @@ pub fn make_command(make_args: &[&str]) -> Command {
             line.push('\'');
         }
         let mut c = Command::new("sh");
-        c.arg("-c").arg(line);
-        return c;
+        c.arg("-c").arg(line).current_dir(project_dir);
+        return Ok(c);
     }
     #[cfg(not(target_os = "windows"))]
     {
         let mut c = Command::new("make");
-        c.args(make_args);
-        c
+        c.args(make_args).current_dir(project_dir);
+        Ok(c)
     }
 }
 
-/// Prepend the SGDK build-tool directories to THIS process's PATH (inherited by the
-/// child make and its recipe commands). On Unix: bundled JRE, gcc toolchain, SGDK/bin.
-/// On Windows: bundled JRE (for `java`) + SGDK/bin (bundled MSYS make.exe +
-/// sh/rm/cp/mkdir/dlls + the m68k gcc.exe + native tools). All derived from the fixed
-/// install layout under `path::config_dir()`.
-///
-/// We must modify the process PATH (not just the child's env) because on Windows the
-/// executable lookup for a bare `make` uses the calling process's PATH. Running make
-/// as a bare name (not an absolute path) keeps MSYS make's `$(MAKE)`/SHELL working.
-pub fn prepend_tool_path() {
-    if !path::is_installed() {
-        eprintln!("❌ SGDK not installed. Please run `sgdkx install` first.");
-        std::process::exit(1);
-    }
-    let sgdk_dir = path::sgdk_dir();
-    let sgdk_bin = sgdk_dir.join("bin");
+/// The environment a build of `project_dir` needs: the SGDK build-tool directories to put in
+/// front of PATH — on Unix the bundled JRE, gcc toolchain and SGDK/bin; on Windows the bundled
+/// JRE (for `java`) + SGDK/bin (bundled MSYS make.exe + sh/rm/cp/mkdir/dlls + the m68k gcc.exe +
+/// native tools) — and the `GDK` the project's Makefile includes from. All derived from the
+/// fixed install layout under `path::config_dir()`; SGDK is the version `project_dir` resolves
+/// to (its sgdkx.toml pin, else the default). `sgdkx env` prints it for shells and editors.
+pub struct ToolEnv {
+    /// Directories to prepend to PATH, in order.
+    pub path: Vec<PathBuf>,
+    /// The SGDK directory, forward-slashed because MSYS make on Windows expects `/` paths.
+    pub gdk: String,
+}
 
+pub fn tool_env(project_dir: &Path) -> Result<ToolEnv> {
+    let sgdk_dir = path::sgdk_dir_for(project_dir)?;
+    if !sgdk_dir.join("bin").is_dir() {
+        return Err(Error::SgdkNotInstalled(None));
+    }
     let mut prepend: Vec<PathBuf> = Vec::new();
     if let Some(jre) = path::jre_dir() {
         prepend.push(jre.join("bin"));
@@ -81,20 +96,35 @@ pub fn prepend_tool_path() {
     if let Some(tc) = path::toolchain_dir() {
         prepend.push(tc.join("bin"));
     }
-    prepend.push(sgdk_bin);
+    prepend.push(sgdk_dir.join("bin"));
+    Ok(ToolEnv {
+        path: prepend,
+        gdk: sgdk_dir.to_string_lossy().replace('\\', "/"),
+    })
+}
 
-    let mut paths = prepend;
+/// Apply `tool_env(project_dir)` to THIS process: prepend the tool dirs to PATH (inherited by
+/// the child make and its recipe commands) and export GDK.
+///
+/// We must modify the process PATH (not just the child's env) because on Windows the
+/// executable lookup for a bare `make` uses the calling process's PATH. Running make
+/// as a bare name (not an absolute path) keeps MSYS make's `$(MAKE)`/SHELL working.
+/// Embedders: like any `set_var`, call it while no other thread reads the environment.
+pub fn prepend_tool_path(project_dir: &Path) -> Result<()> {
+    let env = tool_env(project_dir)?;
+    let mut paths = env.path;
     if let Some(orig) = std::env::var_os("PATH") {
         paths.extend(std::env::split_paths(&orig));
     }
-    let new_path = std::env::join_paths(&paths).expect("failed to build PATH");
+    let new_path = std::env::join_paths(&paths)
+        .map_err(|e| Error::InvalidArgument(format!("cannot put the build tools on PATH: {e}")))?;
     // Export GDK so the project's portable Makefile (`GDK ?= ...`) resolves to THIS install on
-    // every platform. Forward-slashed because MSYS make on Windows expects `/` paths. (Older
-    // Makefiles that hard-assign `GDK = <abs>` keep their value — a makefile `=` beats the env.)
-    let gdk = sgdk_dir.to_string_lossy().replace('\\', "/");
+    // every platform. (Older Makefiles that hard-assign `GDK = <abs>` keep their value — a
+    // makefile `=` beats the env.)
     // SAFETY: sgdkx is single-threaded here; set env right before spawning the build.
     unsafe {
-        std::env::set_var("GDK", &gdk);
+        std::env::set_var("GDK", &env.gdk);
         std::env::set_var("PATH", &new_path);
     }
+    Ok(())
 }
@@ -1,10 +1,19 @@
 pub mod blastem;
+pub mod capture;
 pub mod compile_commands;
+pub mod debug;
+pub mod default;
 pub mod doc;
 pub mod doctor;
+pub mod env;
 pub mod gdb;
 pub mod install;
+pub mod list;
 pub mod make;
 pub mod new;
 pub mod open;
+pub mod run;
+pub mod self_update;
+pub mod test;
 pub mod uninstall;
+pub mod update;
@@ -1,3 +1,4 @@
+use crate::error::{Error, OrExit, Result};
 use clap::Parser;
 use std::fs;
 use std::io::IsTerminal;
@@ -14,49 +15,58 @@ pub struct Args {
 }
 
 pub fn run(args: &Args) {
-    let name: &str = args.name.as_str();
+    create_project(Path::new(&args.name), args.template.as_deref()).or_exit();
+}
 
+/// Create a project at `dest` from an SGDK sample: `template` is its path under SGDK/sample
+/// (e.g. "basics/hello-world"); None picks interactively on a terminal and is an error
+/// otherwise. Writes the editor configs, Makefile, sgdkx.toml pins and compile_commands.json.
+pub fn create_project(dest: &Path, template: Option<&str>) -> Result<()> {
     if !crate::path::is_installed() {
-        eprintln!("❌ SGDK not installed. Please run `sgdkx install` first.");
-        std::process::exit(1);
+        return Err(Error::SgdkNotInstalled(None));
     }
-    let sgdk_path = crate::path::sgdk_dir();
+    let sgdk_path = crate::path::sgdk_dir()?;
 
-    let dest_path = Path::new(name);
-    if dest_path.exists() {
-        eprintln!("❌ '{}' already exists.", name);
-        std::process::exit(1);
+    if dest.exists() {
+        return Err(Error::AlreadyExists(dest.to_path_buf()));
     }
 
     // テンプレート選択（--template 指定 / TTYで対話 / 非TTYはエラー）
-    let template_path = select_template(&sgdk_path, args.template.as_deref());
+    let template_path = select_template(&sgdk_path, template)?;
 
-    println!("📁 Creating project from SGDK template: '{}'", name);
+    println!("📁 Creating project from SGDK template: '{}'", dest.display());
 
     let mut opts = fs_extra::dir::CopyOptions::new();
     opts.copy_inside = true;
-    fs_extra::dir::copy(&template_path, dest_path, &opts).expect("Template copy failed");
+    fs_extra::dir::copy(&template_path, dest, &opts).map_err(|e| {
+        Error::io(format!("cannot copy the template to {}", dest.display()), std::io::Error::other(e))
+    })?;
+
+    println!("✅ Project '{}' created!", dest.display());
 
-    println!("✅ Project '{}' created!", name);
+    // Pin the installed SGDK / BlastEm / toolchain tags (committable, checked by `sgdkx make`);
+    // the editor configs below point at the pinned SGDK
+    create_manifest(dest)?;
 
     // Create .clangd configuration file
-    create_clangd_config(dest_path);
+    create_clangd_config(dest)?;
 
     // Create .vscode/c_cpp_properties.json
-    create_vscode_config(dest_path);
+    create_vscode_config(dest)?;
 
     // Create .vscode/launch.json + tasks.json (source-level gdb debugging)
-    create_vscode_debug_config(dest_path);
+    create_vscode_debug_config(dest)?;
 
     // Create .gitignore
-    create_gitignore(dest_path);
+    create_gitignore(dest)?;
 
     // Create the Makefile (portable + committable; `sgdkx make` sets GDK + the toolchain PATH)
-    create_makefile(dest_path);
+    create_makefile(dest)?;
 
     // Generate compile_commands.json (no external compiledb dependency).
     // base_make_command sets up PATH so `make -nwB` resolves (esp. on Windows).
-    generate_compile_commands(dest_path);
+    generate_compile_commands(dest);
+    Ok(())
 }
 
 /// Collect every template (a dir under SGDK/sample containing `src/`), keyed by its path
@@ -85,38 +95,30 @@ fn collect_templates(sgdk_path: &Path) -> Vec<(String, PathBuf)> {
 
 /// Resolve the template directory: explicit `--template <name>` wins; otherwise an interactive
 /// pick on a terminal; otherwise (non-interactive, no flag) an error listing the templates.
-fn select_template(sgdk_path: &Path, explicit: Option<&str>) -> PathBuf {
+pub fn select_template(sgdk_path: &Path, explicit: Option<&str>) -> Result<PathBuf> {
     let templates = collect_templates(sgdk_path);
     if templates.is_empty() {
-        eprintln!("❌ No templates found in {}", sgdk_path.join("sample").display());
-        std::process::exit(1);
+        return Err(Error::NoTemplates(sgdk_path.join("sample")));
     }
-
-    let list_available = || {
-        eprintln!("Available templates:");
-        for (rel, _) in &templates {
-            eprintln!("  {rel}");
-        }
-    };
+    let available = || templates.iter().map(|(rel, _)| rel.clone()).collect();
 
     if let Some(name) = explicit {
         return match templates.iter().find(|(rel, _)| rel == name) {
             Some((rel, path)) => {
                 println!("Using template: {rel}");
-                path.clone()
-            }
-            None => {
-                eprintln!("❌ template '{name}' not found.");
-                list_available();
-                std::process::exit(1);
+                Ok(path.clone())
             }
+            None => Err(Error::TemplateNotFound {
+                name: name.to_string(),
+                available: available(),
+            }),
         };
     }
 
     if !std::io::stdin().is_terminal() {
-        eprintln!("❌ no template selected. Re-run with --template <name> (required when non-interactive).");
-        list_available();
-        std::process::exit(1);
+        return Err(Error::TemplateRequired {
+            available: available(),
+        });
     }
 
     use dialoguer::{Select, theme::ColorfulTheme};
@@ -126,17 +128,10 @@ fn select_template(sgdk_path: &Path, explicit: Option<&str>) -> PathBuf {
         .items(&items)
         .default(0)
         .interact_opt()
-        .unwrap();
-    match selection {
-        Some(idx) => {
-            println!("Selected template: {}", templates[idx].0);
-            templates[idx].1.clone()
-        }
-        None => {
-            println!("Cancelled.");
-            std::process::exit(0);
-        }
-    }
+        .map_err(|e| Error::io("template prompt failed", std::io::Error::other(e)))?;
+    let idx = selection.ok_or(Error::Cancelled)?;
+    println!("Selected template: {}", templates[idx].0);
+    Ok(templates[idx].1.clone())
 }
 
 /// Generate compile_commands.json by parsing `make -nwB` output (a make dry-run),
@@ -145,9 +140,8 @@ fn select_template(sgdk_path: &Path, explicit: Option<&str>) -> PathBuf {
 pub fn generate_compile_commands(project_path: &Path) {
     println!("🔧 Generating compile_commands.json...");
 
-    let output = match crate::commands::make::make_command(&["-nwB"])
-        .current_dir(project_path)
-        .output()
+    let output = match crate::commands::make::make_command(project_path, &["-nwB"])
+        .and_then(|mut c| c.output().map_err(|e| Error::io("cannot run make", e)))
     {
         Ok(o) => o,
         Err(e) => {
@@ -217,7 +211,7 @@ pub fn generate_compile_commands(project_path: &Path) {
     }
 }
 
-pub fn create_clangd_config(project_path: &Path) {
+pub fn create_clangd_config(project_path: &Path) -> Result<()> {
     println!("📄 Creating .clangd configuration file...");
 
     let clangd_content = r#"# Configuration for using clangd with SGDK projects in Zed Editor (adjustments for GCC-based code)
@@ -239,16 +233,47 @@ Diagnostics:
 "#;
 
     let clangd_path = project_path.join(".clangd");
-    fs::write(clangd_path, clangd_content).expect("Failed to create .clangd file");
+    write_file(&clangd_path, clangd_content)?;
     println!("✅ .clangd configuration file created");
+    Ok(())
+}
+
+/// The install root as the generated editor configs spell it by default.
+const DEFAULT_EDITOR_ROOT: &str = "${userHome}/.sgdkx/data";
+
+/// The install root for the generated `.vscode` files: home-relative by default (portable), or,
+/// with a relocated `SGDKX_HOME`, `${workspaceFolder}/...` when it lies inside the project
+/// (a hermetic project-local install) and its absolute path otherwise.
+fn editor_root(project_path: &Path) -> String {
+    let Some(home) = crate::path::home_override() else {
+        return DEFAULT_EDITOR_ROOT.to_string();
+    };
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let root = match canonical(&home).strip_prefix(canonical(project_path)) {
+        Ok(rel) if rel.as_os_str().is_empty() => "${workspaceFolder}".to_string(),
+        Ok(rel) => format!("${{workspaceFolder}}/{}", rel.display()),
+        Err(_) => canonical(&home).display().to_string(),
+    };
+    root.replace(r"\\?\", "").replace('\\', "/")
 }
 
-pub fn create_vscode_config(project_path: &Path) {
+/// The SGDK directory the generated `.vscode` files name, under `root` (see `editor_root`): the
+/// version the project's sgdkx.toml pins, so the editor sees the SGDK `sgdkx make` builds with
+/// whatever the default is; the default's `SGDK` link for an unpinned project.
+fn editor_sgdk_dir(project_path: &Path, root: &str) -> String {
+    match crate::project::pins_for(project_path).sgdk {
+        Some(tag) => format!("{root}/sdk/{tag}"),
+        None => format!("{root}/SGDK"),
+    }
+}
+
+pub fn create_vscode_config(project_path: &Path) -> Result<()> {
     println!("📄 Creating .vscode/c_cpp_properties.json...");
 
     let vscode_dir = project_path.join(".vscode");
     if !vscode_dir.exists() {
-        fs::create_dir_all(&vscode_dir).expect("Failed to create .vscode directory");
+        fs::create_dir_all(&vscode_dir)
+            .map_err(|e| Error::io(format!("cannot create {}", vscode_dir.display()), e))?;
     }
 
     // cStandard: SGDK builds with gcc's default (no -std) = gnu17 (C17 + GNU extensions) and
@@ -264,7 +289,8 @@ pub fn create_vscode_config(project_path: &Path) {
     //   .c files in compile_commands.json get "identifier uint8_t is undefined" (the compile
     //   command names a bare `m68k-elf-gcc`, which cpptools won't run on its own). This is the
     //   macOS/Linux toolchain path; on Windows gcc lives in SGDK/bin and compile_commands carries
-    //   its absolute path, so a stale path here is only a harmless warning. Home-relative.
+    //   its absolute path, so a stale path here is only a harmless warning. Home-relative (see
+    //   editor_root for a relocated SGDKX_HOME); the SGDK paths name the pinned version.
     let cpp_properties_content = r#"{
     "configurations": [
       {
@@ -275,8 +301,8 @@ pub fn create_vscode_config(project_path: &Path) {
         "intelliSenseMode": "gcc-x86",
         "includePath": [
           "${workspaceFolder}/**",
-          "${userHome}/.sgdkx/data/SGDK/inc",
-          "${userHome}/.sgdkx/data/SGDK/res"
+          "@SGDK_DIR@/inc",
+          "@SGDK_DIR@/res"
         ],
         "defines": [ "SGDK_GCC" ]
       }
@@ -285,18 +311,24 @@ pub fn create_vscode_config(project_path: &Path) {
 }
 "#;
 
+    let root = editor_root(project_path);
+    let cpp_properties_content = cpp_properties_content
+        .replace("@SGDK_DIR@", &editor_sgdk_dir(project_path, &root))
+        .replace(DEFAULT_EDITOR_ROOT, &root);
     let cpp_properties_path = vscode_dir.join("c_cpp_properties.json");
-    fs::write(cpp_properties_path, cpp_properties_content)
-        .expect("Failed to create c_cpp_properties.json");
+    write_file(&cpp_properties_path, &cpp_properties_content)?;
     println!("✅ VS Code C++ configuration file created");
+    Ok(())
 }
 
 /// Write VS Code source-level debug configs (launch.json + tasks.json).
 ///
-/// Portable & committable: paths are home-relative (`${userHome}`) and BlastEm is
+/// Portable & committable: paths are home-relative (`${userHome}`; see `editor_root` for a
+/// relocated SGDKX_HOME) and BlastEm is
 /// launched via `sgdkx blastem`, so there are no machine-specific paths. tasks.json
 /// builds a -O0 debug ROM (so breakpoints/locals are reliable — see the OPT note in
-/// the Makefile), starts BlastEm as a gdb server on localhost:1234, and launch.json
+/// the Makefile), starts BlastEm headless as a gdb server on the project's port (see
+/// `debug_port`; via `sgdkx blastem --gdb-server`, which reports readiness), and launch.json
 /// connects via gdb. The default configs link the lean libmd.a (debug your own code,
 /// small ROM); the "+ SGDK source" config rebuilds with SGDK_DEBUG=1 and, via
 /// sourceFileMap / `set substitute-path` remapping SGDK's CI build path (baked into
@@ -305,19 +337,21 @@ pub fn create_vscode_config(project_path: &Path) {
 /// Requires the gdb-capable BlastEm — the patched build that honors `-D` and
 /// BLASTEM_GDB_PORT. `sgdkx install` provides it; if your BlastEm predates gdb
 /// support, update it (otherwise F5 connects to nothing).
-pub fn create_vscode_debug_config(project_path: &Path) {
+pub fn create_vscode_debug_config(project_path: &Path) -> Result<()> {
     println!("📄 Creating .vscode/launch.json + tasks.json (gdb debugging)...");
 
     let vscode_dir = project_path.join(".vscode");
     if !vscode_dir.exists() {
-        fs::create_dir_all(&vscode_dir).expect("Failed to create .vscode directory");
+        fs::create_dir_all(&vscode_dir)
+            .map_err(|e| Error::io(format!("cannot create {}", vscode_dir.display()), e))?;
     }
 
     // gdb (m68k-elf-gdb) is launched directly by cppdbg, so it needs a real path;
     // BlastEm goes through `sgdkx blastem` so we don't hardcode its location.
     // The sourceFileMap "from" is the fixed path where sgdk-native-builds compiles
-    // SGDK in CI; "to" is the local install. If CI ever moves, this just falls back
-    // to "no source" (harmless).
+    // SGDK in CI (`debug::SGDK_BUILD_DIR`); "to" is the local install of the pinned SGDK
+    // (`editor_sgdk_dir`). If CI ever moves,
+    // this just falls back to "no source" (harmless).
     let launch_json = r#"{
   // Source-level debugging of the ROM in (patched) BlastEm via m68k-elf-gdb.
   // Set breakpoints in src/*.c, press F5, then Continue (▶) to reach them.
@@ -335,12 +369,12 @@ pub fn create_vscode_debug_config(project_path: &Path) {
       "cwd": "${workspaceFolder}",
       "MIMode": "gdb",
       "miDebuggerPath": "${userHome}/.sgdkx/data/m68k-elf-gdb/bin/m68k-elf-gdb",
-      "miDebuggerServerAddress": "localhost:1234",
+      "miDebuggerServerAddress": "localhost:@GDB_PORT@",
       "stopAtConnect": false,
       "externalConsole": false,
       "preLaunchTask": "blastem-gdb",
       "sourceFileMap": {
-        "/Users/runner/work/sgdk-native-builds/sgdk-native-builds/SGDK": "${userHome}/.sgdkx/data/SGDK"
+        "@SGDK_BUILD_DIR@": "@SGDK_DIR@"
       },
       "setupCommands": [
         { "description": "break at main", "text": "-break-insert main", "ignoreFailures": true }
@@ -354,12 +388,12 @@ pub fn create_vscode_debug_config(project_path: &Path) {
       "cwd": "${workspaceFolder}",
       "MIMode": "gdb",
       "miDebuggerPath": "${userHome}/.sgdkx/data/m68k-elf-gdb/bin/m68k-elf-gdb",
-      "miDebuggerServerAddress": "localhost:1234",
+      "miDebuggerServerAddress": "localhost:@GDB_PORT@",
       "stopAtConnect": false,
       "externalConsole": false,
       "preLaunchTask": "blastem-gdb-sgdk",
       "sourceFileMap": {
-        "/Users/runner/work/sgdk-native-builds/sgdk-native-builds/SGDK": "${userHome}/.sgdkx/data/SGDK"
+        "@SGDK_BUILD_DIR@": "@SGDK_DIR@"
       },
       "setupCommands": [
         { "description": "break at main", "text": "-break-insert main", "ignoreFailures": true }
@@ -373,9 +407,11 @@ pub fn create_vscode_debug_config(project_path: &Path) {
   // build-debug      : -O0 debug ROM with DWARF, lean libmd.a (debug your code; small ROM).
   // build-debug-sgdk : same + SGDK_DEBUG=1 -> libmd_debug.a, so you can step into SGDK
   //                    source too (larger ROM, slower link; needs SGDK >= 2.10).
-  // blastem-gdb[-sgdk] : run the patched BlastEm as a gdb server on TCP localhost:1234 after
-  //   the matching build. Blocks until the debugger connects; SDL_AUDIODRIVER=dummy +
-  //   BLASTEM_NO_GUI keep headless launches from stalling on a CoreAudio error dialog.
+  // blastem-gdb[-sgdk] : run the patched BlastEm headless as a gdb server on TCP
+  //   localhost:@GDB_PORT@ after the matching build (`sgdkx blastem --gdb-server` reports when
+  //   it listens). The port was picked for this project by `sgdkx new` and must match
+  //   miDebuggerServerAddress in launch.json: change both if it clashes with something else
+  //   (terminal: `sgdkx debug` picks a free port).
   "version": "2.0.0",
   "tasks": [
     {
@@ -398,14 +434,8 @@ pub fn create_vscode_debug_config(project_path: &Path) {
       "label": "blastem-gdb",
       "type": "shell",
       "command": "sgdkx",
-      "args": ["blastem", "${workspaceFolder}/out/debug/rom.bin", "-D"],
-      "options": {
-        "env": {
-          "BLASTEM_GDB_PORT": "1234",
-          "BLASTEM_NO_GUI": "1",
-          "SDL_AUDIODRIVER": "dummy"
-        }
-      },
+      "args": ["blastem", "--gdb-server", "--port", "@GDB_PORT@", "${workspaceFolder}/out/debug/rom.bin"],
+      "options": { "cwd": "${workspaceFolder}" },
       "dependsOn": "build-debug",
       "isBackground": true,
       "problemMatcher": {
@@ -413,7 +443,7 @@ pub fn create_vscode_debug_config(project_path: &Path) {
         "background": {
           "activeOnStart": true,
           "beginsPattern": ".",
-          "endsPattern": "Waiting for GDB connection"
+          "endsPattern": "gdb server listening on"
         }
       }
     },
@@ -421,14 +451,8 @@ pub fn create_vscode_debug_config(project_path: &Path) {
       "label": "blastem-gdb-sgdk",
       "type": "shell",
       "command": "sgdkx",
-      "args": ["blastem", "${workspaceFolder}/out/debug/rom.bin", "-D"],
-      "options": {
-        "env": {
-          "BLASTEM_GDB_PORT": "1234",
-          "BLASTEM_NO_GUI": "1",
-          "SDL_AUDIODRIVER": "dummy"
-        }
-      },
+      "args": ["blastem", "--gdb-server", "--port", "@GDB_PORT@", "${workspaceFolder}/out/debug/rom.bin"],
+      "options": { "cwd": "${workspaceFolder}" },
       "dependsOn": "build-debug-sgdk",
       "isBackground": true,
       "problemMatcher": {
@@ -436,7 +460,7 @@ pub fn create_vscode_debug_config(project_path: &Path) {
         "background": {
           "activeOnStart": true,
           "beginsPattern": ".",
-          "endsPattern": "Waiting for GDB connection"
+          "endsPattern": "gdb server listening on"
         }
       }
     }
@@ -444,12 +468,47 @@ pub fn create_vscode_debug_config(project_path: &Path) {
 }
 "#;
 
-    fs::write(vscode_dir.join("launch.json"), launch_json).expect("Failed to create launch.json");
-    fs::write(vscode_dir.join("tasks.json"), tasks_json).expect("Failed to create tasks.json");
+    let root = editor_root(project_path);
+    let port = debug_port(project_path).to_string();
+    let launch_json = launch_json
+        .replace("@SGDK_BUILD_DIR@", crate::commands::debug::SGDK_BUILD_DIR)
+        .replace("@SGDK_DIR@", &editor_sgdk_dir(project_path, &root))
+        .replace("@GDB_PORT@", &port)
+        .replace(DEFAULT_EDITOR_ROOT, &root);
+    let tasks_json = tasks_json.replace("@GDB_PORT@", &port);
+    // The tasks run `sgdkx` itself, which finds a relocated install only through SGDKX_HOME.
+    let tasks_json = if root == DEFAULT_EDITOR_ROOT {
+        tasks_json
+    } else {
+        let options = r#""options": { "cwd": "${workspaceFolder}" }"#;
+        let with_env = format!(
+            r#""options": {{ "cwd": "${{workspaceFolder}}", "env": {{ "SGDKX_HOME": "{root}" }} }}"#
+        );
+        tasks_json.replace(options, &with_env)
+    };
+    write_file(&vscode_dir.join("launch.json"), &launch_json)?;
+    write_file(&vscode_dir.join("tasks.json"), &tasks_json)?;
     println!("✅ VS Code debug configuration created (gdb via patched BlastEm)");
+    Ok(())
 }
 
-pub fn create_gitignore(project_path: &Path) {
+/// The gdb port of a project's VS Code debug configs. Derived from the project's path, so
+/// projects debugged side by side get different ports (one fixed port would make the second
+/// F5 fail), and moved past ports that are taken right now.
+fn debug_port(project_path: &Path) -> u16 {
+    let path = std::path::absolute(project_path).unwrap_or_else(|_| project_path.to_path_buf());
+    // FNV-1a: stable across Rust versions, unlike DefaultHasher
+    let hash = path.to_string_lossy().bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    // 20000-29999: clear of well-known services and of the OS's ephemeral range
+    let base = 20_000 + (hash % 10_000) as u16;
+    (base..base + 100)
+        .find(|&p| std::net::TcpListener::bind(("127.0.0.1", p)).is_ok())
+        .unwrap_or(base)
+}
+
+pub fn create_gitignore(project_path: &Path) -> Result<()> {
     println!("📄 Creating .gitignore file...");
 
     // Makefile is now portable (no personal paths) and meant to be committed, so it is NOT
@@ -463,14 +522,27 @@ pub fn create_gitignore(project_path: &Path) {
 "#;
 
     let gitignore_path = project_path.join(".gitignore");
-    fs::write(gitignore_path, gitignore_content).expect("Failed to create .gitignore file");
+    write_file(&gitignore_path, gitignore_content)?;
     println!("✅ .gitignore file created");
+    Ok(())
+}
+
+/// Write `sgdkx.toml`, pinning the project to the SGDK it was created from plus the installed
+/// toolchain/BlastEm tags, so later installs of other versions don't silently change what it
+/// builds against.
+pub fn create_manifest(project_path: &Path) -> Result<()> {
+    println!("📄 Creating {}...", crate::project::MANIFEST_FILE);
+    let mut pins = crate::project::Pins::installed();
+    pins.sgdk = crate::path::active_sgdk_version(Path::new("."));
+    pins.write(project_path)?;
+    println!("✅ {} created", crate::project::MANIFEST_FILE);
+    Ok(())
 }
 
 /// Write the project Makefile. It carries no machine-specific paths — `GDK ?=` defaults to the
-/// unified `~/.sgdkx/data` install and `sgdkx make` sets `GDK` + the build-tool PATH, so the
-/// same file builds on every platform.
-pub fn create_makefile(project_path: &Path) {
+/// unified `~/.sgdkx/data` install (or `$SGDKX_HOME`, when set) and `sgdkx make` sets `GDK` +
+/// the build-tool PATH, so the same file builds on every platform.
+pub fn create_makefile(project_path: &Path) -> Result<()> {
     println!("📄 Creating Makefile...");
 
     let makefile_content = r#"# SGDK project Makefile — generated by sgdkx.
@@ -480,7 +552,8 @@ pub fn create_makefile(project_path: &Path) {
 #   sgdkx make debug OPT=-Og      # debug build, lighter optimization
 #   sgdkx make debug SGDK_DEBUG=1 # also step into SGDK source (needs SGDK >= 2.10)
 #   sgdkx make clean              # remove build artifacts
-GDK ?= $(HOME)/.sgdkx/data/SGDK
+SGDKX_HOME ?= $(HOME)/.sgdkx/data
+GDK ?= $(SGDKX_HOME)/SGDK
 include $(GDK)/makefile.gen
 
 # Tune `make debug` for source-level debugging (SGDK's debug build is -O1 +
@@ -497,6 +570,11 @@ endif
 "#;
 
     let makefile_path = project_path.join("Makefile");
-    fs::write(makefile_path, makefile_content).expect("Failed to create Makefile");
+    write_file(&makefile_path, makefile_content)?;
     println!("✅ Makefile created");
+    Ok(())
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).map_err(|e| Error::io(format!("cannot create {}", path.display()), e))
 }
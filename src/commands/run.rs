@@ -0,0 +1,61 @@
+// `sgdkx run`: build the project and, only if that succeeded, boot the ROM in the bundled
+// BlastEm — the `sgdkx make` + `sgdkx blastem out/rom.bin` pair in one step.
+
+use crate::commands::blastem::require_blastem;
+use crate::commands::make;
+use crate::error::{Error, OrExit, Result};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Build and run the debug ROM (`make debug` → out/debug/rom.bin)
+    #[arg(long)]
+    debug: bool,
+
+    /// Arguments passed to BlastEm after the ROM path (after `--`, e.g. `sgdkx run -- -m gen`)
+    #[arg(last = true)]
+    emulator_args: Vec<String>,
+}
+
+pub fn run(args: &Args) {
+    let project = Path::new(".");
+    let exe = require_blastem(project).or_exit();
+    let rom = build_rom(project, args.debug, &[]).or_exit();
+    println!("🎮 Running {}", rom.display());
+    let status = Command::new(&exe)
+        .arg(&rom)
+        .args(&args.emulator_args)
+        .status()
+        .map_err(|e| Error::io("failed to run BlastEm", e))
+        .or_exit();
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Where the build of `project_dir` writes its ROM: `out/rom.bin`, or `out/debug/rom.bin` for
+/// the debug build.
+pub fn rom_path(project_dir: &Path, debug: bool) -> PathBuf {
+    let out = project_dir.join("out");
+    if debug { out.join("debug") } else { out }.join("rom.bin")
+}
+
+/// Build `project_dir` (`make`, or `make debug`, plus `make_args`) and return the ROM it
+/// produced. A failed build — or one that leaves no ROM behind — is an error.
+pub fn build_rom(project_dir: &Path, debug: bool, make_args: &[&str]) -> Result<PathBuf> {
+    let mut argv: Vec<&str> = Vec::new();
+    if debug {
+        argv.push("debug");
+    }
+    argv.extend_from_slice(make_args);
+    let status = make::build(project_dir, &argv)?;
+    if !status.success() {
+        let code = status.code().map_or("a signal".to_string(), |c| c.to_string());
+        return Err(Error::BuildFailed(format!("make exited with {code}")));
+    }
+    let rom = rom_path(project_dir, debug);
+    if !rom.is_file() {
+        return Err(Error::BuildFailed(format!("make produced no {}", rom.display())));
+    }
+    Ok(rom)
+}
@@ -0,0 +1,183 @@
+// `sgdkx self update`: replace the running sgdkx with the newest release's executable for this
+// platform. The download goes through the same resumable, SHA-256-verified path as the
+// components (and honors a mirror); the new binary must run and report the expected version
+// before it is renamed over the old one, so a bad download never leaves sgdkx unusable. A copy
+// installed by `cargo install` is cargo's to replace: sgdkx points there instead.
+
+use crate::error::OrExit;
+use crate::path;
+use crate::release;
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: SelfCommand,
+}
+
+#[derive(Subcommand)]
+enum SelfCommand {
+    /// Update sgdkx itself to the latest release
+    Update(UpdateArgs),
+}
+
+#[derive(Parser)]
+struct UpdateArgs {
+    /// Only report whether a newer sgdkx is available
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn run(args: &Args) {
+    match &args.command {
+        SelfCommand::Update(a) => update(a),
+    }
+}
+
+fn update(args: &UpdateArgs) {
+    let current = env!("CARGO_PKG_VERSION");
+    if !args.check && cargo_installed() {
+        println!("sgdkx v{current} was installed with cargo; update it with `{CARGO_UPDATE}`.");
+        return;
+    }
+    let tag = match release::resolve_tag(release::SGDKX_REPO, "latest") {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("❌ failed to look up the latest sgdkx release: {e}");
+            std::process::exit(1);
+        }
+    };
+    if !is_newer(&tag, current) {
+        println!("✅ sgdkx v{current} is up to date (latest: {tag})");
+        return;
+    }
+    if args.check {
+        println!("⬆️  sgdkx {tag} is available (installed: v{current}). Run `{}`.", update_command());
+        return;
+    }
+
+    let plat = release::platform().or_exit();
+    let asset = exe_asset(plat);
+    println!("📥 Downloading sgdkx {tag} ({plat})...");
+    let res = release::download_verified(release::SGDKX_REPO, &tag, &asset)
+        .and_then(|(tmp, _)| replace_exe(tmp.path(), &tag));
+    match res {
+        Ok(exe) => println!("✅ sgdkx updated to {tag}: {}", exe.display()),
+        Err(e) => {
+            eprintln!("❌ failed to update sgdkx: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+const CARGO_UPDATE: &str = "cargo install sgdkx";
+
+/// Whether the running sgdkx was installed by `cargo install`: it sits in the `bin/` of a cargo
+/// install root (`~/.cargo`, `$CARGO_HOME` or a `--root`), which cargo tracks in `.crates.toml`.
+fn cargo_installed() -> bool {
+    let Ok(exe) = std::env::current_exe().and_then(|e| e.canonicalize()) else {
+        return false;
+    };
+    let Some(root) = exe.parent().filter(|d| d.ends_with("bin")).and_then(Path::parent) else {
+        return false;
+    };
+    root.join(".crates.toml").is_file() || root.join(".crates2.json").is_file()
+}
+
+/// How the user updates this copy of sgdkx.
+pub fn update_command() -> &'static str {
+    if cargo_installed() { CARGO_UPDATE } else { "sgdkx self update" }
+}
+
+/// Release asset name of the sgdkx executable for `plat`.
+fn exe_asset(plat: &str) -> String {
+    if plat.starts_with("windows") {
+        format!("sgdkx-{plat}.exe")
+    } else {
+        format!("sgdkx-{plat}")
+    }
+}
+
+/// Whether release tag `tag` (`v0.4.5`) is a newer version than `current` (`0.4.4`). Compared
+/// numerically per dotted part; a tag that doesn't parse is never newer.
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse().ok())
+            .collect()
+    };
+    match (parse(tag), parse(current)) {
+        (Some(t), Some(c)) => t > c,
+        _ => false,
+    }
+}
+
+/// Install the verified executable `new` over the running one: copied next to it, checked to
+/// run and report `tag`'s version, then renamed into place (atomic on Unix; on Windows the
+/// running exe can't be overwritten, so it is first renamed aside to `<name>.old`).
+fn replace_exe(new: &Path, tag: &str) -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .and_then(|e| e.canonicalize())
+        .map_err(|e| format!("cannot locate the running sgdkx: {e}"))?;
+    let dir = exe.parent().ok_or("the running sgdkx has no parent directory")?;
+    let name = exe.file_name().ok_or("the running sgdkx has no file name")?;
+    let staged = dir.join(format!(".{}.new", name.to_string_lossy()));
+    fs::copy(new, &staged).map_err(|e| {
+        format!("cannot write to {} ({e}); re-run with permission to replace sgdkx", dir.display())
+    })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755)).map_err(|e| {
+            let _ = fs::remove_file(&staged);
+            format!("cannot make {} executable: {e}", staged.display())
+        })?;
+    }
+
+    let expected = format!("sgdkx {}", tag.trim_start_matches('v'));
+    let runs = Command::new(&staged)
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success() && String::from_utf8_lossy(&o.stdout).trim() == expected);
+    if !runs {
+        let _ = fs::remove_file(&staged);
+        return Err(format!("the downloaded executable doesn't run or isn't {expected}"));
+    }
+
+    #[cfg(windows)]
+    let old = dir.join(format!("{}.old", name.to_string_lossy()));
+    #[cfg(windows)]
+    {
+        let _ = fs::remove_file(&old);
+        fs::rename(&exe, &old).map_err(|e| {
+            let _ = fs::remove_file(&staged);
+            format!("cannot move {} aside: {e}", exe.display())
+        })?;
+    }
+    fs::rename(&staged, &exe).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        // put the running exe back, so a failed update still leaves sgdkx in place
+        #[cfg(windows)]
+        let _ = fs::rename(&old, &exe);
+        format!("cannot replace {}: {e}", exe.display())
+    })?;
+    Ok(exe)
+}
+
+/// The newest sgdkx release tag if it is newer than this binary — for doctor's opt-in notice
+/// (`[update] check = true` in config.toml, or `SGDKX_UPDATE_CHECK=1`). None when not opted in
+/// or the lookup fails (the notice must never get in the way).
+pub fn newer_release() -> Option<String> {
+    let opted_in = std::env::var("SGDKX_UPDATE_CHECK").is_ok_and(|v| v == "1")
+        || path::config_flag("update", "check");
+    if !opted_in {
+        return None;
+    }
+    release::resolve_tag(release::SGDKX_REPO, "latest")
+        .ok()
+        .filter(|tag| is_newer(tag, env!("CARGO_PKG_VERSION")))
+}
@@ -0,0 +1,389 @@
+// `sgdkx test`: automated ROM tests in CI. Each ROM boots in a headless BlastEm serving gdb
+// (`start_gdb_server`), driven over the gdb remote protocol (see `blastem::gdb_remote`): a
+// breakpoint on the VBlank handler stops it once per frame, so it runs a number of frames — or
+// until a RAM marker is set or a breakpoint is hit — then memory is read back and compared,
+// e.g. the test-result word the game writes. One pass/fail line per ROM, optionally a JUnit
+// XML report.
+
+use crate::commands::blastem::gdb_remote::Remote;
+use crate::commands::blastem::{parse_number, require_blastem, start_gdb_server};
+use crate::commands::run;
+use crate::error::{Error, OrExit, Result};
+use clap::Parser;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Parser)]
+pub struct Args {
+    /// ROMs to test (default: build the project and test out/rom.bin)
+    roms: Vec<PathBuf>,
+
+    /// Frames to run — the limit when waiting with --until / --break
+    #[arg(long, default_value_t = 600)]
+    frames: u64,
+
+    /// Run until a RAM value is set: ADDR[.b|.w|.l]=VALUE (e.g. 0xFF0000=1; size defaults to .w)
+    #[arg(long, value_name = "ADDR=VALUE", value_parser = parse_check, conflicts_with = "break_at")]
+    until: Option<Check>,
+
+    /// Run until the 68k reaches ADDR (a breakpoint, e.g. 0x2a4)
+    #[arg(long = "break", value_name = "ADDR", value_parser = parse_address)]
+    break_at: Option<u32>,
+
+    /// Then assert a memory value: ADDR[.b|.w|.l]=VALUE (repeatable)
+    #[arg(long, value_name = "ADDR=VALUE", value_parser = parse_check)]
+    expect: Vec<Check>,
+
+    /// Also write a JUnit XML report to FILE
+    #[arg(long, value_name = "FILE")]
+    junit: Option<PathBuf>,
+
+    /// Seconds BlastEm gets to start, and to get from one frame to the next
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    timeout: u64,
+}
+
+/// A memory location and the big-endian value expected there.
+#[derive(Clone, Debug)]
+struct Check {
+    addr: u32,
+    /// Bytes: 1 (`.b`), 2 (`.w`) or 4 (`.l`).
+    size: u8,
+    value: u32,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match self.size {
+            1 => "b",
+            4 => "l",
+            _ => "w",
+        };
+        write!(f, "{:#x}.{suffix}", self.addr)
+    }
+}
+
+fn parse_address(s: &str) -> std::result::Result<u32, String> {
+    parse_number(s)
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or_else(|| format!("'{s}' is not an address (decimal or 0x-prefixed hex)"))
+}
+
+fn parse_check(s: &str) -> std::result::Result<Check, String> {
+    let (location, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("'{s}' is not ADDR[.b|.w|.l]=VALUE"))?;
+    let (addr, size) = match location.rsplit_once('.') {
+        Some((addr, "b")) => (addr, 1),
+        Some((addr, "w")) => (addr, 2),
+        Some((addr, "l")) => (addr, 4),
+        Some((_, size)) => return Err(format!("unknown size '.{size}' (use .b, .w or .l)")),
+        None => (location, 2),
+    };
+    let value = parse_address(value)?;
+    if size < 4 && value >> (size * 8) != 0 {
+        return Err(format!("{value:#x} does not fit in {size} byte(s)"));
+    }
+    Ok(Check {
+        addr: parse_address(addr)?,
+        size,
+        value,
+    })
+}
+
+/// What one ROM's run came to.
+enum Verdict {
+    Pass(String),
+    Fail(String),
+    /// The test couldn't run (BlastEm failed to start, protocol error).
+    Error(Error),
+}
+
+struct Outcome {
+    rom: PathBuf,
+    time: Duration,
+    verdict: Verdict,
+}
+
+pub fn run(args: &Args) {
+    let exe = require_blastem(Path::new(".")).or_exit();
+    let roms = if args.roms.is_empty() {
+        vec![run::build_rom(Path::new("."), false, &[]).or_exit()]
+    } else {
+        args.roms.clone()
+    };
+
+    println!("🧪 Testing {} ROM(s) in headless BlastEm...", roms.len());
+    let mut outcomes = Vec::new();
+    for rom in roms {
+        let start = Instant::now();
+        let verdict = if rom.is_file() {
+            test_rom(&exe, &rom, args).unwrap_or_else(Verdict::Error)
+        } else {
+            Verdict::Error(Error::InvalidArgument(format!("{} not found", rom.display())))
+        };
+        let time = start.elapsed();
+        match &verdict {
+            Verdict::Pass(detail) => {
+                println!("✅ {} ({detail}, {:.1}s)", rom.display(), time.as_secs_f64())
+            }
+            Verdict::Fail(reason) => println!("❌ {}: {reason}", rom.display()),
+            Verdict::Error(e) => println!("❌ {}: {e}", rom.display()),
+        }
+        outcomes.push(Outcome { rom, time, verdict });
+    }
+
+    if let Some(file) = &args.junit {
+        write_junit(file, &outcomes).or_exit();
+        println!("📝 JUnit report written to {}", file.display());
+    }
+    let failed = outcomes
+        .iter()
+        .filter(|o| !matches!(o.verdict, Verdict::Pass(_)))
+        .count();
+    if failed > 0 {
+        eprintln!("❌ {failed} of {} ROM(s) failed", outcomes.len());
+        std::process::exit(1);
+    }
+    println!("✅ All {} ROM(s) passed", outcomes.len());
+}
+
+/// Boot `rom`, run it as `args` say, then check the `--expect` values.
+fn test_rom(exe: &Path, rom: &Path, args: &Args) -> Result<Verdict> {
+    let timeout = Duration::from_secs(args.timeout);
+    let server = start_gdb_server(exe, rom, &[], None, false, timeout)?;
+    let mut session = Session::start(server.port, timeout, args.break_at)?;
+    let target = match (&args.until, args.break_at) {
+        (Some(marker), _) => Some(format!("{marker} = {:#x}", marker.value)),
+        (None, Some(addr)) => Some(format!("breakpoint {addr:#x}")),
+        (None, None) => None,
+    };
+    loop {
+        if let Some(marker) = &args.until
+            && session.value(marker)? == marker.value
+        {
+            break;
+        }
+        if session.frame >= args.frames {
+            if let Some(target) = target {
+                let frames = session.frame;
+                return Ok(Verdict::Fail(format!("{target} not reached in {frames} frames")));
+            }
+            break;
+        }
+        let pc = session.next_stop()?;
+        if args.break_at == Some(pc) {
+            break;
+        }
+    }
+
+    let frame = session.frame;
+    for check in &args.expect {
+        let value = session.value(check)?;
+        if value != check.value {
+            return Ok(Verdict::Fail(format!(
+                "{check} is {value:#x}, expected {:#x} (frame {frame})",
+                check.value
+            )));
+        }
+    }
+    session.remote.kill();
+    Ok(Verdict::Pass(format!("frame {frame}")))
+}
+
+/// The 68k's level-6 autovector: the VBlank interrupt handler, entered once per frame.
+const VBLANK_VECTOR: u32 = 0x78;
+
+/// A ROM stopped in BlastEm's gdb server, run from breakpoint to breakpoint. One sits on the
+/// VBlank handler, so it never runs longer than a frame and the frames can be counted.
+struct Session {
+    remote: Remote,
+    vblank: u32,
+    breakpoints: Vec<u32>,
+    /// The breakpoint it is stopped at, which it has to step past before continuing.
+    at: Option<u32>,
+    /// VBlanks so far.
+    frame: u64,
+}
+
+impl Session {
+    /// Connect to the gdb server on `port` and set the VBlank breakpoint, plus one at
+    /// `break_at`.
+    fn start(port: u16, timeout: Duration, break_at: Option<u32>) -> Result<Session> {
+        let mut remote = Remote::connect(port, timeout)?;
+        let vblank = be_value(&remote.read_memory(VBLANK_VECTOR, 4)?);
+        let mut breakpoints = vec![vblank];
+        breakpoints.extend(break_at.filter(|&addr| addr != vblank));
+        for &addr in &breakpoints {
+            remote.insert_breakpoint(addr)?;
+        }
+        Ok(Session {
+            remote,
+            vblank,
+            breakpoints,
+            at: None,
+            frame: 0,
+        })
+    }
+
+    /// Run to the next breakpoint and return its address.
+    fn next_stop(&mut self) -> Result<u32> {
+        // as gdb does: lift the breakpoint for one instruction, which may land on another
+        if let Some(at) = self.at.take() {
+            self.remote.remove_breakpoint(at)?;
+            self.remote.step()?;
+            self.remote.insert_breakpoint(at)?;
+            let pc = self.remote.pc()?;
+            if self.breakpoints.contains(&pc) {
+                return Ok(self.stopped_at(pc));
+            }
+        }
+        self.remote.resume()?;
+        let pc = self.remote.pc()?;
+        if !self.breakpoints.contains(&pc) {
+            return Err(Error::Emulator(format!("the 68k stopped at {pc:#x}, not at a breakpoint")));
+        }
+        Ok(self.stopped_at(pc))
+    }
+
+    fn stopped_at(&mut self, pc: u32) -> u32 {
+        self.at = Some(pc);
+        if pc == self.vblank {
+            self.frame += 1;
+        }
+        pc
+    }
+
+    /// The value at `check`'s location.
+    fn value(&mut self, check: &Check) -> Result<u32> {
+        Ok(be_value(&self.remote.read_memory(check.addr, check.size as usize)?))
+    }
+}
+
+/// Big-endian bytes as a number.
+fn be_value(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |v, &b| (v << 8) | u32::from(b))
+}
+
+/// A JUnit XML report (one `<testcase>` per ROM), as CI systems import it.
+fn write_junit(file: &Path, outcomes: &[Outcome]) -> Result<()> {
+    let count = |f: fn(&Verdict) -> bool| outcomes.iter().filter(|o| f(&o.verdict)).count();
+    let failures = count(|v| matches!(v, Verdict::Fail(_)));
+    let errors = count(|v| matches!(v, Verdict::Error(_)));
+    let total: f64 = outcomes.iter().map(|o| o.time.as_secs_f64()).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += &format!(
+        "<testsuites tests=\"{n}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{total:.3}\">\n  \
+         <testsuite name=\"sgdkx test\" tests=\"{n}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{total:.3}\">\n",
+        n = outcomes.len()
+    );
+    for o in outcomes {
+        let name = xml_escape(&o.rom.display().to_string());
+        let time = o.time.as_secs_f64();
+        let open = format!("    <testcase classname=\"sgdkx.test\" name=\"{name}\" time=\"{time:.3}\"");
+        xml += &match &o.verdict {
+            Verdict::Pass(_) => format!("{open}/>\n"),
+            Verdict::Fail(reason) => {
+                format!("{open}>\n      <failure message=\"{}\"/>\n    </testcase>\n", xml_escape(reason))
+            }
+            Verdict::Error(e) => format!(
+                "{open}>\n      <error message=\"{}\"/>\n    </testcase>\n",
+                xml_escape(&e.to_string())
+            ),
+        };
+    }
+    xml += "  </testsuite>\n</testsuites>\n";
+
+    if let Some(dir) = file.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| Error::io(format!("cannot create {}", dir.display()), e))?;
+    }
+    std::fs::write(file, xml).map_err(|e| Error::io(format!("cannot write {}", file.display()), e))
+}
+
+/// `s` escaped for an XML attribute. Control characters XML 1.0 can't carry at all are
+/// dropped; tab, newline and carriage return become character references so they survive
+/// attribute normalization.
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push_str(&format!("&#{};", c as u32)),
+            c if c.is_control() && c < ' ' => {}
+            '\u{fffe}' | '\u{ffff}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_sizes_default_to_word() {
+        let check = parse_check("0xFF0000=1").unwrap();
+        assert_eq!((check.addr, check.size, check.value), (0xFF0000, 2, 1));
+        assert_eq!(check.to_string(), "0xff0000.w");
+        for (suffix, size) in [("b", 1), ("w", 2), ("l", 4)] {
+            let check = parse_check(&format!("0xff0010.{suffix}=0x12")).unwrap();
+            assert_eq!((check.addr, check.size, check.value), (0xff0010, size, 0x12));
+            assert_eq!(check.to_string(), format!("0xff0010.{suffix}"));
+        }
+        assert!(parse_check("0xff0000.q=1").unwrap_err().contains("unknown size"));
+        assert!(parse_check("0xff0000").is_err());
+        assert!(parse_check("ram=1").is_err());
+    }
+
+    #[test]
+    fn check_values_must_fit_their_size() {
+        assert!(parse_check("0xff0000.b=255").is_ok());
+        assert!(parse_check("0xff0000.b=256").unwrap_err().contains("does not fit"));
+        assert!(parse_check("0xff0000.w=0xffff").is_ok());
+        assert!(parse_check("0xff0000=0x10000").is_err());
+        assert!(parse_check("0xff0000.l=0xffffffff").is_ok());
+        assert!(parse_check("0xff0000.l=0x100000000").is_err());
+    }
+
+    #[test]
+    fn xml_escape_keeps_the_report_well_formed() {
+        assert_eq!(xml_escape(r#"a<b & "c">'d'"#), "a&lt;b &amp; &quot;c&quot;&gt;&apos;d&apos;");
+        assert_eq!(xml_escape("one\ntwo\tthree\r"), "one&#10;two&#9;three&#13;");
+        // BlastEm output can carry escape sequences and NULs, which XML 1.0 can't
+        assert_eq!(xml_escape("\u{1b}[31mred\u{0}\u{7}"), "[31mred");
+        assert_eq!(xml_escape("é ✓"), "é ✓");
+    }
+
+    #[test]
+    fn junit_report_counts_failures_and_errors() {
+        let outcome = |rom: &str, verdict| Outcome {
+            rom: PathBuf::from(rom),
+            time: Duration::from_millis(1500),
+            verdict,
+        };
+        let outcomes = [
+            outcome("pass.bin", Verdict::Pass("frame 600".into())),
+            outcome("fail.bin", Verdict::Fail("0xff0000.w is 0x2, expected <1>".into())),
+            outcome("error.bin", Verdict::Error(Error::Emulator("exited\u{0}".into()))),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("reports/junit.xml");
+        write_junit(&file, &outcomes).unwrap();
+
+        let xml = std::fs::read_to_string(file).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(r#"<testsuites tests="3" failures="1" errors="1" time="4.500">"#));
+        assert!(xml.contains(r#"<testcase classname="sgdkx.test" name="pass.bin" time="1.500"/>"#));
+        assert!(xml.contains(r#"<failure message="0xff0000.w is 0x2, expected &lt;1&gt;"/>"#));
+        assert!(xml.contains(r#"<error message="exited"/>"#), "{xml}");
+        assert!(xml.ends_with("  </testsuite>\n</testsuites>\n"));
+    }
+}
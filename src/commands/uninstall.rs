@@ -1,3 +1,4 @@
+use crate::error::OrExit;
 use crate::path;
 use clap::Parser;
 use std::fs;
@@ -8,35 +9,42 @@ pub struct Args {
     /// Skip the confirmation prompt (required when non-interactive)
     #[arg(short = 'y', long = "yes")]
     yes: bool,
+
+    /// Remove only these components (comma-separated) instead of the whole environment.
+    /// `docs` removes the documentation of every installed SGDK version.
+    #[arg(
+        long = "component",
+        value_delimiter = ',',
+        value_parser = clap::builder::PossibleValuesParser::new(REMOVABLE)
+    )]
+    components: Vec<String>,
 }
 
+/// Components that can be removed on their own (SGDK versions go with the whole environment).
+const REMOVABLE: [&str; 5] = ["toolchain", "gdb", "jre", "docs", "blastem"];
+
 pub fn run(args: &Args) {
     let config_dir = path::config_dir();
 
-    // Destructive: confirm unless --yes. On a terminal, prompt y/N; non-interactively, require
-    // --yes rather than reading stdin (which would hang or read EOF in a pipeline/CI).
-    if !args.yes {
-        if !std::io::stdin().is_terminal() {
-            eprintln!("❌ refusing to uninstall non-interactively. Re-run with --yes to confirm.");
-            std::process::exit(1);
-        }
-        use std::io::{self, Write};
-        println!("⚠️  Completely remove the SGDK environment and config?");
-        print!("[y/N]> ");
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let input = input.trim().to_lowercase();
-        if input != "y" && input != "yes" {
-            println!("❌ Operation cancelled.");
-            return;
-        }
+    let question = if args.components.is_empty() {
+        "Completely remove the SGDK environment and config?".to_string()
+    } else {
+        format!("Remove {} from the SGDK environment?", args.components.join(", "))
+    };
+    if !args.yes && !confirm(&question) {
+        println!("❌ Operation cancelled.");
+        return;
+    }
+
+    if !args.components.is_empty() && config_dir.exists() {
+        remove_components(&config_dir, &args.components);
+        return;
     }
 
     // Everything (SGDK, toolchain, JRE, gdb, BlastEm, config.toml) lives under config_dir,
     // so a single recursive remove cleans it all — no need to read config.toml or remove
     // components individually.
-    if config_dir.exists() {
+    if config_dir.exists() && args.components.is_empty() {
         println!("🗑️  Removing {}", config_dir.display());
         fs::remove_dir_all(&config_dir).expect("Failed to remove config directory");
         println!("✅ SGDK environment and configuration removed");
@@ -44,3 +52,58 @@ pub fn run(args: &Args) {
         println!("⚠️  Nothing to remove (no install found at {})", config_dir.display());
     }
 }
+
+/// Destructive: confirm unless --yes. On a terminal, prompt y/N; non-interactively, require
+/// --yes rather than reading stdin (which would hang or read EOF in a pipeline/CI).
+fn confirm(question: &str) -> bool {
+    if !std::io::stdin().is_terminal() {
+        eprintln!("❌ refusing to uninstall non-interactively. Re-run with --yes to confirm.");
+        std::process::exit(1);
+    }
+    use std::io::{self, Write};
+    println!("⚠️  {question}");
+    print!("[y/N]> ");
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    let input = input.trim().to_lowercase();
+    input == "y" || input == "yes"
+}
+
+/// Remove individual components and their config.toml entries (`sgdkx install --only <name>`
+/// brings one back).
+fn remove_components(config_dir: &std::path::Path, components: &[String]) {
+    for component in components {
+        let dirs = match component.as_str() {
+            "toolchain" => vec![config_dir.join("m68k-elf-toolchain")],
+            "gdb" => vec![config_dir.join("m68k-elf-gdb")],
+            "jre" => vec![config_dir.join("jre")],
+            "blastem" => vec![config_dir.join("blastem")],
+            // every installed version's docs (plus a not-yet-migrated legacy `SGDK/` directory)
+            "docs" => {
+                let legacy = path::default_sgdk_link().or_exit();
+                let legacy_is_real_dir = fs::symlink_metadata(&legacy).is_ok_and(|m| m.is_dir());
+                path::installed_sgdk_versions()
+                    .iter()
+                    .map(|tag| path::sgdk_version_dir(tag).or_exit().join("doc"))
+                    .chain(legacy_is_real_dir.then(|| legacy.join("doc")))
+                    .collect()
+            }
+            _ => unreachable!("clap restricts --component"),
+        };
+        let present: Vec<_> = dirs.into_iter().filter(|d| d.exists()).collect();
+        if present.is_empty() {
+            println!("⚠️  {component}: not installed");
+        }
+        for dir in present {
+            println!("🗑️  Removing {}", dir.display());
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                eprintln!("❌ failed to remove {}: {e}", dir.display());
+                std::process::exit(1);
+            }
+        }
+        // `docs` drops the whole `[docs.<tag>]` table along with every version's docs
+        crate::commands::install::write_config(config_dir, &[(component.as_str(), None)]).or_exit();
+    }
+    println!("✅ Removed {}", components.join(", "));
+}
@@ -0,0 +1,197 @@
+// `sgdkx update`: compare what config.toml records as installed against what is available —
+// the newest master for a master SGDK, the newest release for a tagged one, the newest build of
+// the BlastEm source it came from, and the tags this sgdkx pins for the toolchain, gdb and JRE —
+// then upgrade just the stale components. Inside a project, the toolchain and BlastEm follow its
+// sgdkx.toml pins instead (installing anything else would make the project refuse to build).
+// `--check` only reports (`--json` for nightly jobs).
+
+use crate::commands::install;
+use crate::path;
+use crate::project::{self, Pins};
+use crate::release;
+use clap::Parser;
+use serde_json::json;
+
+#[derive(Parser)]
+pub struct Args {
+    /// Only report which components are outdated; don't install anything
+    #[arg(long)]
+    check: bool,
+
+    /// Print the report as JSON (with --check)
+    #[arg(long, requires = "check")]
+    json: bool,
+}
+
+/// One installed component against its newest available release.
+struct Component {
+    name: &'static str,
+    repo: String,
+    installed: String,
+    /// The newest release — or the project's pin — or why it couldn't be determined.
+    latest: Result<String, String>,
+    /// `latest` is the version the project's sgdkx.toml pins.
+    pinned: bool,
+}
+
+impl Component {
+    fn outdated(&self) -> bool {
+        self.latest.as_ref().is_ok_and(|l| *l != self.installed)
+    }
+}
+
+pub fn run(args: &Args) {
+    let components = check();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&components)).unwrap());
+    } else {
+        print_report(&components);
+    }
+    let failed = components.iter().any(|c| c.latest.is_err());
+    if !args.check {
+        let stale: Vec<&Component> = components.iter().filter(|c| c.outdated()).collect();
+        if stale.is_empty() {
+            println!("✅ Everything is up to date.");
+        } else if !upgrade(&stale) {
+            std::process::exit(1);
+        }
+    }
+    if failed {
+        if !args.json {
+            eprintln!("\n❌ Could not look up every component's latest release.");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Install the newest release of each stale component. False if any upgrade failed.
+fn upgrade(stale: &[&Component]) -> bool {
+    let config_dir = path::config_dir();
+    let mut ok = true;
+    for c in stale {
+        let latest = c.latest.as_ref().expect("only outdated components are upgraded");
+        println!("\n⬆️  Updating {} {} → {latest}...", c.name, c.installed);
+        match install::upgrade(&config_dir, c.name, &c.repo, latest) {
+            Ok(()) => println!("✅ {} updated to {latest}", c.name),
+            Err(e) => {
+                eprintln!("❌ {e}");
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Every component config.toml records, with its newest available release. Components that
+/// aren't installed are left out (`sgdkx install` adds them).
+fn check() -> Vec<Component> {
+    let mut components = Vec::new();
+    // a malformed manifest is reported by the commands that build; here it just pins nothing
+    let pins: Pins = project::pins_for(std::path::Path::new("."));
+
+    // the default SGDK follows its channel: master builds → newest master, tags → newest tag
+    if let Some(tag) = path::installed_version() {
+        let repo = release::SGDK_NATIVE_REPO;
+        let latest = if tag.starts_with("master-") {
+            release::latest_master_tag(repo)
+        } else {
+            release::list_release_tags(repo).and_then(|tags| {
+                tags.into_iter()
+                    .find(|t| !t.starts_with("master-"))
+                    .ok_or_else(|| format!("no tagged release found in {repo}"))
+            })
+        };
+        components.push(Component {
+            name: "sgdk",
+            repo: repo.to_string(),
+            installed: tag,
+            latest,
+            // versions live side by side: a new default doesn't touch a pinned one
+            pinned: false,
+        });
+    }
+
+    let fixed = [
+        ("toolchain", release::TOOLCHAIN_REPO, release::TOOLCHAIN_TAG),
+        ("gdb", release::GDB_REPO, release::GDB_TAG),
+        ("jre", release::JRE_REPO, release::JRE_TAG),
+    ];
+    for (name, repo, tag) in fixed {
+        if let Some(record) = path::install_record(name) {
+            let pin = (name == "toolchain").then(|| pins.toolchain.clone()).flatten();
+            components.push(Component {
+                name,
+                repo: record.repo.unwrap_or_else(|| repo.to_string()),
+                installed: record.version,
+                pinned: pin.is_some(),
+                latest: Ok(pin.unwrap_or_else(|| tag.to_string())),
+            });
+        }
+    }
+
+    // BlastEm stays on the source it was installed from (debug-capable fork or upstream
+    // nightly) — unless the project pins it, which may name the other source
+    if let Some(record) = path::install_record("blastem") {
+        let (repo, latest) = match &pins.blastem {
+            Some(pin) => {
+                let (repo, tag) = release::blastem_source(pin);
+                (repo.to_string(), release::resolve_tag(repo, &tag))
+            }
+            None => {
+                let repo = record
+                    .repo
+                    .unwrap_or_else(|| release::blastem_source(&record.version).0.to_string());
+                let latest = release::resolve_tag(&repo, "latest");
+                (repo, latest)
+            }
+        };
+        components.push(Component {
+            name: "blastem",
+            repo,
+            installed: record.version,
+            latest,
+            pinned: pins.blastem.is_some(),
+        });
+    }
+    components
+}
+
+fn print_report(components: &[Component]) {
+    if components.is_empty() {
+        println!("Nothing installed. Please run `sgdkx install`.");
+        return;
+    }
+    for c in components {
+        let pinned = if c.pinned { ", pinned by sgdkx.toml" } else { "" };
+        match &c.latest {
+            Ok(latest) if c.outdated() => {
+                let pinned = if c.pinned { " (pinned by sgdkx.toml)" } else { "" };
+                println!("⬆️  {:<10}: {} → {latest}{pinned}", c.name, c.installed)
+            }
+            Ok(_) => println!("✅ {:<10}: {} (up to date{pinned})", c.name, c.installed),
+            Err(e) => println!("⚠️  {:<10}: {} (latest unknown: {e})", c.name, c.installed),
+        }
+    }
+}
+
+/// `{ outdated, components: [{ name, repo, installed, latest, pinned, outdated, error }] }`
+fn to_json(components: &[Component]) -> serde_json::Value {
+    let list: Vec<_> = components
+        .iter()
+        .map(|c| {
+            json!({
+                "name": c.name,
+                "repo": c.repo,
+                "installed": c.installed,
+                "latest": c.latest.as_ref().ok(),
+                "pinned": c.pinned,
+                "outdated": c.outdated(),
+                "error": c.latest.as_ref().err(),
+            })
+        })
+        .collect();
+    json!({
+        "outdated": components.iter().any(Component::outdated),
+        "components": list,
+    })
+}
@@ -0,0 +1,164 @@
+// Errors returned by the library API (install, project generation, build environment). The CLI
+// prints them as `❌ <error>` and exits non-zero (see `OrExit`); embedders match on the variant.
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// No `SGDKX_HOME` and no home directory to put `~/.sgdkx/data` under.
+    NoHomeDir,
+    /// The SGDK version in effect (or the given one) isn't installed.
+    SgdkNotInstalled(Option<String>),
+    /// A bundled tool (`BlastEm`, `m68k-elf-gdb`) isn't installed.
+    ToolNotFound(&'static str),
+    /// A project's sgdkx.toml can't be read or parsed.
+    Manifest(String),
+    /// A project's sgdkx.toml pins a different version than the installed one (`installed` is
+    /// None for a pinned SGDK that isn't installed at all).
+    PinMismatch {
+        manifest: PathBuf,
+        component: &'static str,
+        pinned: String,
+        installed: Option<String>,
+    },
+    /// The SGDK install has no sample projects to use as templates.
+    NoTemplates(PathBuf),
+    /// `--template` names no sample.
+    TemplateNotFound { name: String, available: Vec<String> },
+    /// No template given and no terminal to pick one on.
+    TemplateRequired { available: Vec<String> },
+    /// The project directory to create already exists.
+    AlreadyExists(PathBuf),
+    /// `make` failed, or didn't produce the ROM.
+    BuildFailed(String),
+    /// BlastEm failed to start or misbehaved.
+    Emulator(String),
+    /// The user cancelled an interactive pick.
+    Cancelled,
+    /// A value the user passed is not valid (e.g. an unknown platform slug).
+    InvalidArgument(String),
+    /// A release lookup failed (network, rate limit, missing tag).
+    Release(String),
+    /// Downloading or installing a component failed.
+    Install { component: String, reason: String },
+    /// A filesystem operation failed.
+    Io {
+        context: String,
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    /// An `Io` error for `context` (e.g. "cannot create /path").
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn install(component: &str, reason: impl Into<String>) -> Self {
+        Error::Install {
+            component: component.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// CLI handling: print the error and exit 1 — or exit 0 after "Cancelled." for a cancelled
+    /// prompt, which is the user's choice rather than a failure.
+    pub fn exit(&self) -> ! {
+        if let Error::Cancelled = self {
+            println!("{self}");
+            std::process::exit(0);
+        }
+        eprintln!("❌ {self}");
+        std::process::exit(1);
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, available: &[String]| {
+            write!(f, "\nAvailable templates:")?;
+            available.iter().try_for_each(|t| write!(f, "\n  {t}"))
+        };
+        match self {
+            Error::NoHomeDir => {
+                write!(f, "cannot determine the home directory; set SGDKX_HOME (or pass --home <dir>)")
+            }
+            Error::SgdkNotInstalled(None) => {
+                write!(f, "SGDK not installed. Please run `sgdkx install` first.")
+            }
+            Error::SgdkNotInstalled(Some(tag)) => {
+                write!(f, "SGDK {tag} is not installed. Run `sgdkx install --sgdk {tag}`.")
+            }
+            Error::ToolNotFound(tool) => {
+                write!(f, "{tool} not found. Run `sgdkx install` to download it.")
+            }
+            Error::Manifest(e) => write!(f, "failed to read {e}"),
+            Error::PinMismatch {
+                manifest,
+                component,
+                pinned,
+                installed: None,
+            } => write!(
+                f,
+                "{} pins {component} {pinned}, which is not installed.\n   \
+                 Run `sgdkx install --sgdk {pinned}` (or `sgdkx install` in the project).",
+                manifest.display()
+            ),
+            Error::PinMismatch {
+                manifest,
+                component,
+                pinned,
+                installed: Some(installed),
+            } => write!(
+                f,
+                "{} pins {component} {pinned}, but {installed} is installed.\n   \
+                 Run `sgdkx install` in the project to install the pinned versions.",
+                manifest.display()
+            ),
+            Error::NoTemplates(dir) => write!(f, "No templates found in {}", dir.display()),
+            Error::TemplateNotFound { name, available } => {
+                write!(f, "template '{name}' not found.")?;
+                list(f, available)
+            }
+            Error::TemplateRequired { available } => {
+                write!(
+                    f,
+                    "no template selected. Re-run with --template <name> (required when non-interactive)."
+                )?;
+                list(f, available)
+            }
+            Error::AlreadyExists(dir) => write!(f, "'{}' already exists.", dir.display()),
+            Error::BuildFailed(e) => write!(f, "build failed: {e}"),
+            Error::Cancelled => write!(f, "Cancelled."),
+            Error::InvalidArgument(e) | Error::Release(e) | Error::Emulator(e) => write!(f, "{e}"),
+            Error::Install { component, reason } => write!(f, "failed to install {component}: {reason}"),
+            Error::Io { context, source } => write!(f, "{context}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Unwrap a library result at the CLI layer, reporting the error and exiting (`Error::exit`).
+pub trait OrExit<T> {
+    fn or_exit(self) -> T;
+}
+
+impl<T> OrExit<T> for Result<T> {
+    fn or_exit(self) -> T {
+        self.unwrap_or_else(|e| e.exit())
+    }
+}
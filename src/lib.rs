@@ -0,0 +1,36 @@
+//! sgdkx as a library: the same install, project generation, build environment and tool
+//! discovery the `sgdkx` CLI runs, for embedding in build tooling and editor plugins. The entry
+//! points re-exported here return `sgdkx::Result` and never exit the process; they still print
+//! the CLI's progress lines to stdout/stderr.
+//!
+//! `commands` is public for the `sgdkx` binary and for the `Args` structs the entry points
+//! take. The subcommands' `run` functions in it are the CLI's: they report an error and exit,
+//! so embedders call the re-exports instead.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! // SGDK v2.11 + the debug-capable BlastEm, without prompting
+//! let args = sgdkx::commands::install::Args {
+//!     sgdk: Some("v2.11".into()),
+//!     blastem: Some("latest".into()),
+//!     ..Default::default()
+//! };
+//! sgdkx::install(&args)?;
+//! sgdkx::create_project(Path::new("mygame"), Some("basics/hello-world"))?;
+//! let status = sgdkx::make_command(Path::new("mygame"), &["release"])?.status();
+//! # Ok::<(), sgdkx::Error>(())
+//! ```
+
+pub mod commands;
+pub mod error;
+pub mod path;
+pub mod project;
+pub mod release;
+
+pub use commands::blastem::{GdbServer, Headless, find_blastem, start_gdb_server};
+pub use commands::gdb::find_gdb;
+pub use commands::install::{install, install_from};
+pub use commands::make::{ToolEnv, build, make_command, prepend_tool_path, tool_env};
+pub use commands::new::create_project;
+pub use error::{Error, Result};
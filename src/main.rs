@@ -1,24 +1,34 @@
 use clap::{Parser, Subcommand};
 
-mod commands;
-mod path;
-mod release;
-use commands::blastem;
-use commands::compile_commands;
-use commands::doc;
-use commands::doctor;
-use commands::gdb;
-use commands::install;
-use commands::make;
-use commands::new;
-use commands::open;
-use commands::uninstall;
+use sgdkx::commands::blastem;
+use sgdkx::commands::capture;
+use sgdkx::commands::compile_commands;
+use sgdkx::commands::debug;
+use sgdkx::commands::default;
+use sgdkx::commands::doc;
+use sgdkx::commands::doctor;
+use sgdkx::commands::env;
+use sgdkx::commands::gdb;
+use sgdkx::commands::install;
+use sgdkx::commands::list;
+use sgdkx::commands::make;
+use sgdkx::commands::new;
+use sgdkx::commands::open;
+use sgdkx::commands::run;
+use sgdkx::commands::self_update;
+use sgdkx::commands::test;
+use sgdkx::commands::uninstall;
+use sgdkx::commands::update;
 
 /// One-command native SGDK dev environment. Unofficial, cross-platform CLI.
 #[derive(Parser)]
 #[command(name = "sgdkx")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 struct Cli {
+    /// Install root to use instead of ~/.sgdkx/data (same as setting SGDKX_HOME)
+    #[arg(long = "home", value_name = "DIR", global = true)]
+    home: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -28,15 +38,39 @@ enum Commands {
     /// Install/update the self-contained SGDK environment (SGDK, toolchain, JRE, gdb, BlastEm)
     Install(install::Args),
 
+    /// Check for newer component releases and upgrade the stale ones (`--check` only reports)
+    Update(update::Args),
+
+    /// List the SGDK versions installed side by side
+    List(list::Args),
+
+    /// Show or switch the default SGDK version
+    Default(default::Args),
+
     /// Create a new SGDK project
     New(new::Args),
 
     /// Build the project: thin wrapper around make (args passed straight through)
     Make(make::Args),
 
+    /// Print PATH / GDK for the bundled build tools as shell commands (e.g. `eval "$(sgdkx env)"`)
+    Env(env::Args),
+
+    /// Build the project, then run the ROM in BlastEm (`--debug` for the debug build)
+    Run(run::Args),
+
     /// Run the bundled BlastEm (args passed straight through, e.g. out/rom.bin)
     Blastem(blastem::Args),
 
+    /// Build the debug ROM and debug it in m68k-elf-gdb against BlastEm (`--sgdk-source` to step into SGDK)
+    Debug(debug::Args),
+
+    /// Run ROMs headless in BlastEm and check memory for test results (pass/fail per ROM)
+    Test(test::Args),
+
+    /// Run a ROM headless and save PNG screenshots at given frames (`--compare` against golden images)
+    Capture(capture::Args),
+
     /// Run m68k-elf-gdb (args passed straight through, e.g. out/rom.out)
     Gdb(gdb::Args),
 
@@ -44,6 +78,9 @@ enum Commands {
     #[allow(clippy::enum_variant_names)] // name must stay for the `compile-commands` command
     CompileCommands(compile_commands::Args),
 
+    /// Check the environment (non-zero exit if a required component is missing or broken)
+    Doctor(doctor::Args),
+
     /// Show SGDK documentation status
     Doc,
 
@@ -52,29 +89,49 @@ enum Commands {
 
     /// Uninstall the SGDK environment and configuration
     Uninstall(uninstall::Args),
+
+    /// Manage sgdkx itself (`sgdkx self update`)
+    #[command(name = "self")]
+    SelfUpdate(self_update::Args),
 }
 
 fn main() {
     let cli = Cli::parse();
+    if let Some(home) = &cli.home {
+        // exported (absolute) so child processes — make, `sgdkx` run from editor tasks — agree
+        let home = std::path::absolute(home).unwrap_or_else(|_| home.clone());
+        // SAFETY: single-threaded at startup, before anything reads the environment.
+        unsafe { std::env::set_var("SGDKX_HOME", home) };
+    }
 
     match &cli.command {
         Some(cmd) => match cmd {
             Commands::Install(args) => install::run(args),
+            Commands::Update(args) => update::run(args),
+            Commands::List(args) => list::run(args),
+            Commands::Default(args) => default::run(args),
             Commands::New(args) => new::run(args),
             Commands::Make(args) => make::run(args),
+            Commands::Env(args) => env::run(args),
+            Commands::Run(args) => run::run(args),
             Commands::Blastem(args) => blastem::run(args),
+            Commands::Debug(args) => debug::run(args),
+            Commands::Test(args) => test::run(args),
+            Commands::Capture(args) => capture::run(args),
             Commands::Gdb(args) => gdb::run(args),
             Commands::CompileCommands(args) => compile_commands::run(args),
+            Commands::Doctor(args) => doctor::run(args),
             Commands::Doc => doc::run(),
             Commands::Open(args) => open::run(args),
             Commands::Uninstall(args) => uninstall::run(args),
+            Commands::SelfUpdate(args) => self_update::run(args),
         },
         None => {
             // No subcommand: print help (via clap, no subprocess) then the doctor check.
             use clap::CommandFactory;
             let _ = Cli::command().print_help();
             println!();
-            doctor::run();
+            doctor::overview();
         }
     }
 }
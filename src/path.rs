@@ -1,47 +1,189 @@
-use std::path::PathBuf;
+use crate::error::{Error, OrExit, Result};
+use std::path::{Path, PathBuf};
 
 /// Install/config location, unified across platforms: `~/.sgdkx/data`
 /// (home-relative, like cargo/rustup — short and consistent on macOS, Linux, and Windows).
+/// `SGDKX_HOME` (or the global `--home` flag, which sets it) relocates the whole environment,
+/// e.g. to a shared volume in CI or a project-local `.sgdkx`; a relative value is taken from
+/// the current directory.
+pub fn try_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = home_override() {
+        return Ok(dir);
+    }
+    dirs::home_dir()
+        .map(|home| home.join(".sgdkx").join("data"))
+        .ok_or(Error::NoHomeDir)
+}
+
+/// `try_config_dir` for the CLI commands: exits when there is no home directory. Everything the
+/// library reaches goes through `try_config_dir` (the readers below treat a missing home as
+/// nothing installed).
 pub fn config_dir() -> PathBuf {
-    let home = dirs::home_dir().expect("Failed to get home directory");
-    home.join(".sgdkx").join("data")
+    try_config_dir().or_exit()
+}
+
+/// The `SGDKX_HOME` relocation, made absolute, if set.
+pub fn home_override() -> Option<PathBuf> {
+    let dir = PathBuf::from(std::env::var_os("SGDKX_HOME").filter(|h| !h.is_empty())?);
+    Some(std::path::absolute(&dir).unwrap_or(dir))
 }
 
 // Everything sgdkx installs lives at a fixed spot under `config_dir()`, so component paths
-// are derived rather than stored. config.toml keeps only the one non-derivable fact: which
-// SGDK native-build version is installed.
+// are derived rather than stored. config.toml keeps only the non-derivable facts: which
+// release tag of each component (SGDK, toolchain, BlastEm) is installed — for SGDK, several
+// versions sit side by side under `<config>/sdk/<tag>` and config.toml names the default.
+
+/// Root of the side-by-side SGDK installs (`<config>/sdk`).
+pub fn sdk_root() -> Result<PathBuf> {
+    Ok(try_config_dir()?.join("sdk"))
+}
+
+/// Install directory of one SGDK version (`<config>/sdk/<tag>`).
+pub fn sgdk_version_dir(tag: &str) -> Result<PathBuf> {
+    Ok(sdk_root()?.join(tag))
+}
+
+/// `<config>/SGDK`: a link to the default version's directory, so home-relative paths in
+/// generated Makefiles and editor configs keep working. (A real directory here is a
+/// pre-side-by-side install, migrated into `sdk/` by the next `install`/`default`.)
+pub fn default_sgdk_link() -> Result<PathBuf> {
+    Ok(try_config_dir()?.join("SGDK"))
+}
+
+/// The active SGDK directory for the current directory: the version pinned by the enclosing
+/// project's sgdkx.toml, else the default (rustup-style).
+pub fn sgdk_dir() -> Result<PathBuf> {
+    sgdk_dir_for(Path::new("."))
+}
+
+/// The active SGDK directory for the project containing `dir` (see `sgdk_dir`).
+pub fn sgdk_dir_for(dir: &Path) -> Result<PathBuf> {
+    let legacy = default_sgdk_link()?;
+    Ok(match active_sgdk_version(dir) {
+        // the default, not yet migrated out of a legacy `<config>/SGDK` directory
+        Some(tag)
+            if !sgdk_version_dir(&tag)?.is_dir()
+                && Some(&tag) == installed_version().as_ref()
+                && legacy.is_dir() =>
+        {
+            legacy
+        }
+        // (a pinned version that isn't installed resolves to where it would be)
+        Some(tag) => sgdk_version_dir(&tag)?,
+        None => legacy,
+    })
+}
+
+/// The SGDK version in effect for `dir`: the project pin, else the default.
+pub fn active_sgdk_version(dir: &Path) -> Option<String> {
+    crate::project::pins_for(dir).sgdk.or_else(installed_version)
+}
 
-/// The installed SGDK directory (`<config>/SGDK`).
-pub fn sgdk_dir() -> PathBuf {
-    config_dir().join("SGDK")
+/// SGDK versions installed side by side (plus a not-yet-migrated legacy default), sorted by tag.
+pub fn installed_sgdk_versions() -> Vec<String> {
+    let (Ok(root), Ok(legacy)) = (sdk_root(), default_sgdk_link()) else {
+        return Vec::new();
+    };
+    let mut tags: Vec<String> = std::fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().join("bin").is_dir())
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .collect();
+    let legacy_is_real_dir = std::fs::symlink_metadata(&legacy).is_ok_and(|m| m.is_dir());
+    if let Some(default) = installed_version()
+        && legacy_is_real_dir
+        && legacy.join("bin").is_dir()
+        && !tags.contains(&default)
+    {
+        tags.push(default);
+    }
+    tags.sort();
+    tags
 }
 
 /// The bundled gcc toolchain dir, if present. `None` on Windows, where the toolchain lives
 /// inside the SGDK bundle's `bin/` (no separate component).
 pub fn toolchain_dir() -> Option<PathBuf> {
-    let d = config_dir().join("m68k-elf-toolchain");
+    let d = try_config_dir().ok()?.join("m68k-elf-toolchain");
     d.join("bin").is_dir().then_some(d)
 }
 
 /// The bundled JRE dir, if present (else the build falls back to system `java`).
 pub fn jre_dir() -> Option<PathBuf> {
-    let d = config_dir().join("jre");
+    let d = try_config_dir().ok()?.join("jre");
     d.join("bin").is_dir().then_some(d)
 }
 
-/// Whether `sgdkx install` has populated the environment (SGDK is present).
+/// Whether `sgdkx install` has populated the environment (the active SGDK is present).
 pub fn is_installed() -> bool {
-    sgdk_dir().join("bin").is_dir()
+    sgdk_dir().is_ok_and(|d| d.join("bin").is_dir())
 }
 
-/// The installed SGDK native-build version recorded at install time (config.toml's only
-/// field), if any.
+/// The default SGDK version (`sgdkx default`), recorded at install time, if any.
 pub fn installed_version() -> Option<String> {
-    let text = std::fs::read_to_string(config_dir().join("config.toml")).ok()?;
+    installed_version_of("sgdk")
+}
+
+/// The release tag recorded at install time for `component` (`sgdk`, `toolchain`, `gdb`,
+/// `jre`, `blastem`), if any.
+pub fn installed_version_of(component: &str) -> Option<String> {
+    install_record(component).map(|r| r.version)
+}
+
+/// What config.toml records about one installed component: where it came from and when. Older
+/// entries (and the `sgdk` default pointer) carry only the version.
+#[derive(Clone, Default)]
+pub struct InstallRecord {
+    /// Release tag.
+    pub version: String,
+    /// GitHub `owner/name` it was downloaded from.
+    pub repo: Option<String>,
+    pub asset: Option<String>,
+    /// SHA-256 of the downloaded asset.
+    pub sha256: Option<String>,
+    /// Install date (`YYYY-MM-DD`, UTC).
+    pub installed: Option<String>,
+}
+
+/// The config.toml record under `key`: a component name, or `sdk.<tag>` / `docs.<tag>` for
+/// one side-by-side SGDK version and its documentation.
+pub fn install_record(key: &str) -> Option<InstallRecord> {
+    let text = std::fs::read_to_string(try_config_dir().ok()?.join("config.toml")).ok()?;
+    let doc: toml_edit::DocumentMut = text.parse().ok()?;
+    let item = match key.split_once('.') {
+        Some((table, tag)) => doc.get(table)?.get(tag)?,
+        None => doc.get(key)?,
+    };
+    let field = |f: &str| item.get(f).and_then(|v| v.as_str()).map(str::to_string);
+    Some(InstallRecord {
+        version: field("version")?,
+        repo: field("repo"),
+        asset: field("asset"),
+        sha256: field("sha256"),
+        installed: field("installed"),
+    })
+}
+
+/// A boolean setting from config.toml's `[<table>]` (e.g. `[update] check = true`); false if
+/// unset.
+pub fn config_flag(table: &str, key: &str) -> bool {
+    config_value(table, key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// An integer setting from config.toml's `[<table>]` (e.g. `[network] cache_ttl = 600`), if set.
+pub fn config_integer(table: &str, key: &str) -> Option<i64> {
+    config_value(table, key)?.as_integer()
+}
+
+/// A string setting from config.toml's `[<table>]` (e.g. `[network] mirror`), if set.
+pub fn config_setting(table: &str, key: &str) -> Option<String> {
+    config_value(table, key)?.as_str().map(str::to_string)
+}
+
+fn config_value(table: &str, key: &str) -> Option<toml_edit::Item> {
+    let text = std::fs::read_to_string(try_config_dir().ok()?.join("config.toml")).ok()?;
     let doc: toml_edit::DocumentMut = text.parse().ok()?;
-    doc.get("sgdk")?
-        .as_inline_table()?
-        .get("version")?
-        .as_str()
-        .map(str::to_string)
+    doc.get(table)?.get(key).cloned()
 }
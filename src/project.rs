@@ -0,0 +1,242 @@
+// Per-project manifest: `sgdkx.toml` at the project root (written by `sgdkx new`) pins the
+// SGDK / BlastEm / toolchain release tags the project expects. Commands that run project tools
+// (`make`, `compile-commands`, `blastem`, `gdb`) resolve against the pins, so one machine can
+// keep an older game on v2.00 while a new one tracks master: the pinned SGDK is picked from the
+// side-by-side installs, the other components must match what is installed.
+
+use crate::error::{Error, Result};
+use crate::path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, value};
+
+pub const MANIFEST_FILE: &str = "sgdkx.toml";
+
+/// The release tags a project pins. A missing entry means "whatever is installed".
+#[derive(Default)]
+pub struct Pins {
+    pub sgdk: Option<String>,
+    pub blastem: Option<String>,
+    pub toolchain: Option<String>,
+}
+
+impl Pins {
+    /// The versions currently recorded in config.toml — what `sgdkx new` pins a fresh project
+    /// to. Unknown entries (e.g. installed by an older sgdkx) are left unpinned.
+    pub fn installed() -> Pins {
+        Pins {
+            sgdk: path::installed_version_of(Component::Sgdk.key()),
+            blastem: path::installed_version_of(Component::Blastem.key()),
+            toolchain: path::installed_version_of(Component::Toolchain.key()),
+        }
+    }
+
+    pub fn get(&self, component: Component) -> Option<&str> {
+        match component {
+            Component::Sgdk => self.sgdk.as_deref(),
+            Component::Blastem => self.blastem.as_deref(),
+            Component::Toolchain => self.toolchain.as_deref(),
+        }
+    }
+
+    /// Parse a manifest file.
+    pub fn load(file: &Path) -> Result<Pins> {
+        let text = fs::read_to_string(file)
+            .map_err(|e| Error::Manifest(format!("{}: {e}", file.display())))?;
+        let doc: DocumentMut = text
+            .parse()
+            .map_err(|e| Error::Manifest(format!("{}: invalid TOML: {e}", file.display())))?;
+        let tag = |c: Component| -> Result<Option<String>> {
+            let Some(tag) = doc.get(c.key()).and_then(|v| v.as_str()) else {
+                return Ok(None);
+            };
+            if !c.is_channel(tag) {
+                return Ok(Some(tag.to_string()));
+            }
+            // installs are recorded under the tag the channel resolved to, so a channel never
+            // matches what is installed
+            let example = c
+                .installed_in_channel(tag)
+                .map_or("the tag `sgdkx install` reports".to_string(), |t| format!("\"{t}\""));
+            Err(Error::Manifest(format!(
+                "{}: {} = \"{tag}\" names a release channel, not a release; pin a concrete tag \
+                 instead, e.g. {example}",
+                file.display(),
+                c.key()
+            )))
+        };
+        Ok(Pins {
+            sgdk: tag(Component::Sgdk)?,
+            blastem: tag(Component::Blastem)?,
+            toolchain: tag(Component::Toolchain)?,
+        })
+    }
+
+    /// Write `<project_dir>/sgdkx.toml` (only the pinned entries).
+    pub fn write(&self, project_dir: &Path) -> Result<()> {
+        let mut doc = DocumentMut::new();
+        for c in [Component::Sgdk, Component::Blastem, Component::Toolchain] {
+            if let Some(tag) = self.get(c) {
+                doc.insert(c.key(), value(tag));
+            }
+        }
+        let text = format!(
+            "# Release tags this project builds against (see `sgdkx install`).\n{doc}"
+        );
+        let file = project_dir.join(MANIFEST_FILE);
+        fs::write(&file, text).map_err(|e| Error::io(format!("cannot write {}", file.display()), e))
+    }
+}
+
+/// A component a project can pin.
+#[derive(Clone, Copy)]
+pub enum Component {
+    Sgdk,
+    Blastem,
+    Toolchain,
+}
+
+impl Component {
+    /// Key in both sgdkx.toml and config.toml.
+    pub fn key(self) -> &'static str {
+        match self {
+            Component::Sgdk => "sgdk",
+            Component::Blastem => "blastem",
+            Component::Toolchain => "toolchain",
+        }
+    }
+
+    /// Whether `tag` names a moving release channel (`latest`, SGDK's `master`, BlastEm's
+    /// `nightly`) rather than one release.
+    fn is_channel(self, tag: &str) -> bool {
+        match self {
+            Component::Sgdk => tag == "latest" || tag == "master",
+            Component::Blastem => tag == "latest" || tag == "nightly",
+            Component::Toolchain => tag == "latest",
+        }
+    }
+
+    /// The installed release of `channel`, if one is installed: the concrete tag to pin.
+    fn installed_in_channel(self, channel: &str) -> Option<String> {
+        match (self, channel) {
+            (Component::Sgdk, "master") => {
+                let installed = path::installed_sgdk_versions();
+                let default = path::installed_version().filter(|d| installed.contains(d));
+                default
+                    .filter(|d| d.starts_with("master-"))
+                    .or_else(|| installed.into_iter().rfind(|t| t.starts_with("master-")))
+            }
+            (Component::Sgdk, _) => path::installed_version(),
+            (Component::Blastem, _) => path::installed_version_of(self.key())
+                .filter(|v| v.starts_with("nightly-") == (channel == "nightly")),
+            (Component::Toolchain, _) => path::installed_version_of(self.key()),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Component::Sgdk => "SGDK",
+            Component::Blastem => "BlastEm",
+            Component::Toolchain => "gcc toolchain",
+        }
+    }
+}
+
+/// The nearest `sgdkx.toml` in `start` or one of its ancestors.
+pub fn find_manifest(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    start
+        .ancestors()
+        .map(|d| d.join(MANIFEST_FILE))
+        .find(|f| f.is_file())
+}
+
+/// The pins of the project containing `dir` (all empty outside a project). A malformed
+/// manifest is an error rather than silently building against the wrong SDK.
+pub fn load_pins(dir: &Path) -> Result<Pins> {
+    match find_manifest(dir) {
+        Some(file) => Pins::load(&file),
+        None => Ok(Pins::default()),
+    }
+}
+
+/// `load_pins` for resolving paths (which SGDK is active): a malformed manifest counts as no
+/// pins here. Everything that builds or runs project tools checks it first (`require_pinned`),
+/// which reports the error.
+pub fn pins_for(dir: &Path) -> Pins {
+    load_pins(dir).unwrap_or_default()
+}
+
+/// Refuse to run a project's tools against a different version of `component` than the
+/// project pins. An install that predates version recording can't be checked, so it only
+/// warns.
+pub fn require_pinned(dir: &Path, component: Component) -> Result<()> {
+    // Windows has no separate toolchain: gcc ships inside the SGDK bundle (pinned with it).
+    if cfg!(target_os = "windows") && matches!(component, Component::Toolchain) {
+        return Ok(());
+    }
+    let Some(file) = find_manifest(dir) else {
+        return Ok(());
+    };
+    let pins = Pins::load(&file)?;
+    let Some(pinned) = pins.get(component) else {
+        return Ok(());
+    };
+    let mismatch = |installed| Error::PinMismatch {
+        manifest: file.clone(),
+        component: component.label(),
+        pinned: pinned.to_string(),
+        installed,
+    };
+    // SGDK versions live side by side: any installed one can serve the pin.
+    if let Component::Sgdk = component {
+        if !path::sgdk_dir_for(dir)?.join("bin").is_dir() {
+            return Err(mismatch(None));
+        }
+        return Ok(());
+    }
+    match path::installed_version_of(component.key()) {
+        Some(installed) if installed == pinned => {}
+        Some(installed) => return Err(mismatch(Some(installed))),
+        None => eprintln!(
+            "⚠️  installed {} version unknown (re-run `sgdkx install`); can't verify the pin {pinned} in {}",
+            component.label(),
+            file.display()
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(text: &str) -> Result<Pins> {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(MANIFEST_FILE);
+        fs::write(&file, text).unwrap();
+        Pins::load(&file)
+    }
+
+    #[test]
+    fn concrete_pins_load() {
+        let pins = load("sgdk = \"master-1a2b3c\"\nblastem = \"nightly-4d5e6f\"\n").unwrap();
+        assert_eq!(pins.sgdk.as_deref(), Some("master-1a2b3c"));
+        assert_eq!(pins.blastem.as_deref(), Some("nightly-4d5e6f"));
+        assert_eq!(pins.toolchain, None);
+    }
+
+    #[test]
+    fn channel_pins_are_rejected() {
+        let channels = [
+            "sgdk = \"master\"",
+            "blastem = \"nightly\"",
+            "blastem = \"latest\"",
+            "toolchain = \"latest\"",
+        ];
+        for text in channels {
+            let err = load(text).err().map(|e| e.to_string()).unwrap_or_default();
+            assert!(err.contains("names a release channel"), "{text}: {err}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// `sgdkx::install` against a mirror fixture: a temp directory in the mirror layout, passed as
+// the release source, with SGDKX_HOME pointing at another temp directory. The toolchain is the
+// component under test (a Unix-only download, fatal on failure); the project manifest pins it
+// to a fixture tag so no digest pinned in sgdkx applies.
+
+#![cfg(unix)]
+
+use sgdkx::commands::install::{self, Args};
+use sgdkx::release::{self, Mirror};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+const TAG: &str = "gcc13.2.0-fixture";
+
+/// SGDKX_HOME and the current directory are process-wide, so the tests take turns.
+static ENV: Mutex<()> = Mutex::new(());
+
+struct Fixture {
+    _lock: MutexGuard<'static, ()>,
+    _dir: tempfile::TempDir,
+    mirror: PathBuf,
+    home: PathBuf,
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let lock = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let (mirror, home, project) =
+            (dir.path().join("mirror"), dir.path().join("home"), dir.path().join("project"));
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("sgdkx.toml"), format!("toolchain = \"{TAG}\"\n")).unwrap();
+        // SAFETY: the ENV lock is held, so no other test reads or writes the environment
+        unsafe { std::env::set_var("SGDKX_HOME", &home) };
+        std::env::set_current_dir(&project).unwrap();
+        Fixture {
+            _lock: lock,
+            _dir: dir,
+            mirror,
+            home,
+        }
+    }
+
+    /// Publish `archive` as the toolchain asset, with `sha256` as its SHA256SUMS line.
+    fn publish(&self, archive: &[u8], sha256: &str) {
+        let dir = self.mirror.join(release::TOOLCHAIN_REPO).join(TAG);
+        fs::create_dir_all(&dir).unwrap();
+        let asset = release::toolchain_asset(TAG, release::platform().unwrap());
+        fs::write(dir.join(&asset), archive).unwrap();
+        fs::write(dir.join(release::SHA256SUMS), format!("{sha256}  {asset}\n")).unwrap();
+    }
+
+    fn install(&self) -> sgdkx::Result<()> {
+        let args = Args {
+            only: vec!["toolchain".into()],
+            ..Default::default()
+        };
+        let source = Arc::new(Mirror::new(&self.mirror.display().to_string()));
+        install::install_from(&args, source)
+    }
+
+    fn toolchain(&self) -> PathBuf {
+        self.home.join("m68k-elf-toolchain")
+    }
+}
+
+/// A `.tar.gz` of `(path, contents)` files. Paths are written into the header verbatim, so
+/// they may hold `..`.
+fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let mut tar = tar::Builder::new(gz);
+    for (path, contents) in files {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        tar.append(&header, contents.as_bytes()).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap()
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn assert_no_staging(home: &Path) {
+    assert!(!home.join(".staging").exists(), "staging dir left behind");
+}
+
+#[test]
+fn installs_and_records_a_verified_toolchain() {
+    let fx = Fixture::new();
+    let archive = tar_gz(&[("m68k-elf-toolchain/bin/m68k-elf-gcc", "new")]);
+    fx.publish(&archive, &sha256(&archive));
+
+    fx.install().unwrap();
+    let gcc = fs::read_to_string(fx.toolchain().join("bin/m68k-elf-gcc")).unwrap();
+    assert_eq!(gcc, "new");
+    let config = fs::read_to_string(fx.home.join("config.toml")).unwrap();
+    assert!(config.contains(TAG) && config.contains(&sha256(&archive)), "{config}");
+    assert_no_staging(&fx.home);
+}
+
+#[test]
+fn checksum_mismatch_installs_nothing() {
+    let fx = Fixture::new();
+    let archive = tar_gz(&[("m68k-elf-toolchain/bin/m68k-elf-gcc", "tampered")]);
+    fx.publish(&archive, &sha256(b"what the release published"));
+
+    let err = fx.install().unwrap_err().to_string();
+    assert!(err.contains("checksum mismatch"), "{err}");
+    assert!(!fx.toolchain().exists());
+    let config = fs::read_to_string(fx.home.join("config.toml")).unwrap_or_default();
+    assert!(!config.contains("toolchain"), "{config}");
+    assert_no_staging(&fx.home);
+    // the rejected download isn't kept around to be resumed
+    let downloads = fs::read_dir(fx.home.join("downloads")).map_or(0, |d| d.count());
+    assert_eq!(downloads, 0);
+}
+
+#[test]
+fn failed_upgrade_keeps_the_installed_toolchain() {
+    let fx = Fixture::new();
+    let good = tar_gz(&[("m68k-elf-toolchain/bin/m68k-elf-gcc", "old")]);
+    fx.publish(&good, &sha256(&good));
+    fx.install().unwrap();
+
+    // a new release under the same tag whose archive has the wrong layout; the recorded
+    // version no longer matches, so the next install replaces the toolchain
+    let config = fx.home.join("config.toml");
+    let text = fs::read_to_string(&config).unwrap().replace(TAG, "gcc13.2.0-older");
+    fs::write(&config, text).unwrap();
+    let bad = tar_gz(&[("toolchain/bin/m68k-elf-gcc", "new")]);
+    fx.publish(&bad, &sha256(&bad));
+
+    let err = fx.install().unwrap_err().to_string();
+    assert!(err.contains("unexpected layout"), "{err}");
+    let gcc = fs::read_to_string(fx.toolchain().join("bin/m68k-elf-gcc")).unwrap();
+    assert_eq!(gcc, "old");
+    assert_no_staging(&fx.home);
+}
+
+#[test]
+fn interrupted_swap_is_rolled_back() {
+    let fx = Fixture::new();
+    // killed after the old tree was parked and before the new one moved in, with another
+    // component half extracted
+    let parked = fx.home.join(".staging/m68k-elf-toolchain.old/bin");
+    fs::create_dir_all(&parked).unwrap();
+    fs::write(parked.join("m68k-elf-gcc"), "old").unwrap();
+    fs::create_dir_all(fx.home.join(".staging/jre/jre")).unwrap();
+
+    install::recover_interrupted(&fx.home);
+    let gcc = fs::read_to_string(fx.toolchain().join("bin/m68k-elf-gcc")).unwrap();
+    assert_eq!(gcc, "old");
+    assert!(!fx.home.join("jre").exists());
+    assert_no_staging(&fx.home);
+}
+
+#[test]
+fn archive_paths_leaving_the_install_are_rejected() {
+    let fx = Fixture::new();
+    let archive = tar_gz(&[
+        ("m68k-elf-toolchain/bin/m68k-elf-gcc", "new"),
+        ("../../escaped", "pwned"),
+    ]);
+    fx.publish(&archive, &sha256(&archive));
+
+    let err = fx.install().unwrap_err().to_string();
+    assert!(err.contains("leaves the extraction directory"), "{err}");
+    assert!(!fx.toolchain().exists());
+    for dir in [fx.home.join(".staging"), fx.home.clone(), fx.home.parent().unwrap().into()] {
+        assert!(!dir.join("escaped").exists(), "{} written", dir.display());
+    }
+    assert_no_staging(&fx.home);
+}
+
+#[test]
+fn download_only_with_only_is_an_error_not_a_panic() {
+    let fx = Fixture::new();
+    let args = Args {
+        download_only: Some(fx.mirror.join("out")),
+        only: vec!["toolchain".into()],
+        ..Default::default()
+    };
+    let source = Arc::new(Mirror::new(&fx.mirror.display().to_string()));
+    let err = install::install_from(&args, source).unwrap_err();
+    assert!(matches!(err, sgdkx::Error::InvalidArgument(_)), "{err}");
+}